@@ -0,0 +1,89 @@
+//! Neo account encodings and the contract's `i64` account ids.
+//!
+//! A script hash is handled in its serialized (little-endian) byte order, the order the contract
+//! reads its first eight bytes in. Neo tools print it reversed, as `0x`-prefixed big-endian hex.
+
+/// `AddressVersion` byte of Neo N3 addresses.
+pub const ADDRESS_VERSION: u8 = 0x35;
+
+/// The contract's id for `script_hash`: its first eight little-endian bytes.
+pub fn account_id(script_hash: &[u8; 20]) -> i64 {
+    let mut head = [0u8; 8];
+    head.copy_from_slice(&script_hash[..8]);
+    i64::from_le_bytes(head)
+}
+
+/// Script hash from `0x`-prefixed big-endian hex, or `None` when malformed.
+pub fn script_hash_from_hex(text: &str) -> Option<[u8; 20]> {
+    let digits = text.strip_prefix("0x")?.as_bytes();
+    if digits.len() != 40 {
+        return None;
+    }
+    let mut hash = [0u8; 20];
+    for (i, pair) in digits.chunks_exact(2).enumerate() {
+        let byte = (hex_value(pair[0])? << 4) | hex_value(pair[1])?;
+        hash[19 - i] = byte;
+    }
+    Some(hash)
+}
+
+/// Script hash behind a Base58Check N3 address, or `None` when malformed, of another version or
+/// failing its checksum.
+#[cfg(feature = "memory")]
+pub fn script_hash_from_address(address: &str) -> Option<[u8; 20]> {
+    const ALPHABET: &[u8; 58] = b"123456789ABCDEFGHJKLMNPQRSTUVWXYZabcdefghijkmnopqrstuvwxyz";
+
+    // version (1) || script hash (20) || checksum (4)
+    let mut raw = [0u8; 25];
+    for c in address.bytes() {
+        let mut carry = ALPHABET.iter().position(|&a| a == c)? as u32;
+        for byte in raw.iter_mut().rev() {
+            carry += u32::from(*byte) * 58;
+            *byte = carry as u8;
+            carry >>= 8;
+        }
+        if carry != 0 {
+            return None;
+        }
+    }
+
+    let checksum = crate::memory::sha256(&crate::memory::sha256(&raw[..21]));
+    if raw[0] != ADDRESS_VERSION || raw[21..] != checksum[..4] {
+        return None;
+    }
+    let mut hash = [0u8; 20];
+    hash.copy_from_slice(&raw[1..21]);
+    Some(hash)
+}
+
+fn hex_value(digit: u8) -> Option<u8> {
+    match digit {
+        b'0'..=b'9' => Some(digit - b'0'),
+        b'a'..=b'f' => Some(digit - b'a' + 10),
+        b'A'..=b'F' => Some(digit - b'A' + 10),
+        _ => None,
+    }
+}
+
+#[cfg(all(test, feature = "memory"))]
+mod tests {
+    use super::*;
+    use crate::constants::GAS_TOKEN;
+
+    #[test]
+    fn gas_address_and_hex_map_to_the_gas_account_id() {
+        let from_hex = script_hash_from_hex("0xd2a4cff31913016155e38e474a2c06d08be276cf").unwrap();
+        let from_address = script_hash_from_address("NepwUjd9GhqgNkrfXaxj9mmsFhFzGoFuWM").unwrap();
+        assert_eq!(from_hex, from_address);
+        assert_eq!(account_id(&from_hex), GAS_TOKEN);
+
+        assert_eq!(
+            script_hash_from_address("NepwUjd9GhqgNkrfXaxj9mmsFhFzGoFuWN"),
+            None
+        );
+        assert_eq!(
+            script_hash_from_hex("d2a4cff31913016155e38e474a2c06d08be276cf"),
+            None
+        );
+    }
+}
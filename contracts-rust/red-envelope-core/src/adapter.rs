@@ -0,0 +1,117 @@
+use crate::constants::*;
+
+/// Envelope parameters decoded from an `onNEP17Payment` data payload.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PaymentTerms {
+    pub packet_count: i64,
+    pub envelope_type: i64,
+    pub expiry_ms: i64,
+    pub flags: i64,
+    pub start_delay_ms: i64,
+}
+
+/// A deposit adding funds (and optionally packets and time) to an existing envelope.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct TopUpTerms {
+    pub envelope_id: i64,
+    pub add_packets: i64,
+    pub extend_ms: i64,
+}
+
+/// A deposit split evenly into `count` identical envelopes.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct BatchTerms {
+    pub count: i64,
+    pub packet_count: i64,
+    pub envelope_type: i64,
+    pub expiry_ms: i64,
+}
+
+/// Returns the top-up this payload describes, or `None` for envelope-creating payloads.
+pub fn decode_top_up(data: i64) -> Option<TopUpTerms> {
+    if data > -ON_NEP17_TOPUP_BASE {
+        return None;
+    }
+
+    let packed = data.saturating_abs() - ON_NEP17_TOPUP_BASE;
+    Some(TopUpTerms {
+        envelope_id: packed % ON_NEP17_TOPUP_PACKETS_MULTIPLIER,
+        add_packets: packed % ON_NEP17_TOPUP_EXTEND_MULTIPLIER / ON_NEP17_TOPUP_PACKETS_MULTIPLIER,
+        extend_ms: (packed / ON_NEP17_TOPUP_EXTEND_MULTIPLIER)
+            .saturating_mul(ON_NEP17_ADAPTER_MINUTE_MS),
+    })
+}
+
+/// Returns the batch this payload describes, or `None` for single-envelope payloads.
+pub fn decode_batch(data: i64, default_expiry_ms: i64) -> Option<BatchTerms> {
+    if data > -ON_NEP17_BATCH_BASE || data <= -ON_NEP17_ADAPTER_BASE {
+        return None;
+    }
+
+    let packed = data.saturating_abs() - ON_NEP17_BATCH_BASE;
+    let expiry_minutes = packed / ON_NEP17_BATCH_EXPIRY_MULTIPLIER;
+    Some(BatchTerms {
+        count: packed % ON_NEP17_BATCH_EXPIRY_MULTIPLIER / ON_NEP17_BATCH_COUNT_MULTIPLIER,
+        packet_count: packed % ON_NEP17_BATCH_TYPE_MULTIPLIER,
+        envelope_type: packed % ON_NEP17_BATCH_COUNT_MULTIPLIER / ON_NEP17_BATCH_TYPE_MULTIPLIER,
+        expiry_ms: if expiry_minutes > 0 {
+            expiry_minutes.saturating_mul(ON_NEP17_ADAPTER_MINUTE_MS)
+        } else {
+            default_expiry_ms
+        },
+    })
+}
+
+/// Decodes every supported payload encoding; callers still validate the result.
+pub fn decode_payment(data: i64, default_expiry_ms: i64) -> PaymentTerms {
+    let mut terms = PaymentTerms {
+        packet_count: 1,
+        envelope_type: ENVELOPE_TYPE_SPREADING,
+        expiry_ms: default_expiry_ms,
+        flags: 0,
+        start_delay_ms: 0,
+    };
+
+    // Adapter v3 object[] encoding:
+    // spread => BASE + packetCount + expiryMs * MULTIPLIER
+    // pool   => BASE + TYPE_MULTIPLIER + packetCount + expiryMs * MULTIPLIER
+    // equal  => BASE + 3 * TYPE_MULTIPLIER + packetCount + expiryMs * MULTIPLIER
+    // Optional envelope flags ride above the type marker: + flags * FLAGS_MULTIPLIER,
+    // and an optional start delay above those: + startDelayMinutes * START_MULTIPLIER.
+    if data >= ON_NEP17_ADAPTER_BASE {
+        let mut packed = data - ON_NEP17_ADAPTER_BASE;
+        terms.start_delay_ms =
+            (packed / ON_NEP17_ADAPTER_START_MULTIPLIER).saturating_mul(ON_NEP17_ADAPTER_MINUTE_MS);
+        packed %= ON_NEP17_ADAPTER_START_MULTIPLIER;
+        terms.flags = packed / ON_NEP17_ADAPTER_FLAGS_MULTIPLIER;
+        packed %= ON_NEP17_ADAPTER_FLAGS_MULTIPLIER;
+        terms.envelope_type = packed / ON_NEP17_ADAPTER_TYPE_MULTIPLIER;
+        packed %= ON_NEP17_ADAPTER_TYPE_MULTIPLIER;
+        terms.packet_count = packed % ON_NEP17_ADAPTER_EXPIRY_MULTIPLIER;
+        let adapter_expiry = packed / ON_NEP17_ADAPTER_EXPIRY_MULTIPLIER;
+        if adapter_expiry > 0 {
+            terms.expiry_ms = adapter_expiry;
+        }
+    // Backward-compat for older sign-based adapter payloads.
+    } else if data <= -ON_NEP17_ADAPTER_BASE {
+        let packed = data.saturating_abs() - ON_NEP17_ADAPTER_BASE;
+        terms.packet_count = packed % ON_NEP17_ADAPTER_EXPIRY_MULTIPLIER;
+        let adapter_expiry = packed / ON_NEP17_ADAPTER_EXPIRY_MULTIPLIER;
+        if adapter_expiry > 0 {
+            terms.expiry_ms = adapter_expiry;
+        }
+        terms.envelope_type = ENVELOPE_TYPE_POOL;
+    } else if data > 0 {
+        // Legacy packed-integer path for backward compatibility.
+        let packed_packets = data / ON_NEP17_LEGACY_PACK_BASE;
+        if packed_packets > 0 {
+            terms.packet_count = packed_packets;
+        }
+        let packed_type = data - packed_packets * ON_NEP17_LEGACY_PACK_BASE;
+        if packed_type == ENVELOPE_TYPE_POOL {
+            terms.envelope_type = ENVELOPE_TYPE_POOL;
+        }
+    }
+
+    terms
+}
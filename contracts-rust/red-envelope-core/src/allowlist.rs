@@ -0,0 +1,105 @@
+//! Merkle allowlists over account ids, hashed with the same folded SHA-256 as `sha256_pair`.
+//!
+//! Pairs are hashed in sorted order, so a proof is just the sibling at each level and the
+//! contract can fold it one sibling per call without direction bits.
+
+#[cfg(feature = "memory")]
+use alloc::vec::Vec;
+
+use crate::constants::ENVELOPE_FLAG_ALLOWLIST;
+use crate::keys::{env, k3, P_ALLOWLIST_NODE, P_ALLOWLIST_ROOT, P_FLAGS};
+use crate::runtime::Runtime;
+
+pub fn allowlist_leaf<R: Runtime + ?Sized>(rt: &R, account: i64) -> i64 {
+    rt.sha256_pair(0, account)
+}
+
+pub fn merkle_parent<R: Runtime + ?Sized>(rt: &R, a: i64, b: i64) -> i64 {
+    rt.sha256_pair(a.max(b), a.min(b))
+}
+
+/// Whether `account`'s folded proof (or bare leaf, for a single-member list) matches the root.
+pub fn allowlist_proven<R: Runtime + ?Sized>(rt: &R, pool_id: i64, account: i64) -> bool {
+    if env(rt, P_FLAGS, pool_id) & ENVELOPE_FLAG_ALLOWLIST == 0 {
+        return true;
+    }
+
+    let root = env(rt, P_ALLOWLIST_ROOT, pool_id);
+    if root == 0 {
+        return false;
+    }
+
+    let node = rt.get(k3(P_ALLOWLIST_NODE, pool_id, account));
+    if node != 0 {
+        node == root
+    } else {
+        allowlist_leaf(rt, account) == root
+    }
+}
+
+/// Root over `accounts` in the given order; a lone node at the end of a level is promoted as is.
+#[cfg(feature = "memory")]
+pub fn merkle_root<R: Runtime + ?Sized>(rt: &R, accounts: &[i64]) -> i64 {
+    let mut level: Vec<i64> = accounts.iter().map(|a| allowlist_leaf(rt, *a)).collect();
+    if level.is_empty() {
+        return 0;
+    }
+    while level.len() > 1 {
+        level = next_level(rt, &level);
+    }
+    level[0]
+}
+
+/// Siblings to feed `proveAllowlist` in order, or `None` when `account` is not listed.
+#[cfg(feature = "memory")]
+pub fn merkle_proof<R: Runtime + ?Sized>(
+    rt: &R,
+    accounts: &[i64],
+    account: i64,
+) -> Option<Vec<i64>> {
+    let mut index = accounts.iter().position(|a| *a == account)?;
+    let mut level: Vec<i64> = accounts.iter().map(|a| allowlist_leaf(rt, *a)).collect();
+    let mut proof = Vec::new();
+    while level.len() > 1 {
+        let sibling = index ^ 1;
+        if sibling < level.len() {
+            proof.push(level[sibling]);
+        }
+        level = next_level(rt, &level);
+        index /= 2;
+    }
+    Some(proof)
+}
+
+#[cfg(feature = "memory")]
+fn next_level<R: Runtime + ?Sized>(rt: &R, level: &[i64]) -> Vec<i64> {
+    level
+        .chunks(2)
+        .map(|pair| match pair {
+            [a, b] => merkle_parent(rt, *a, *b),
+            _ => pair[0],
+        })
+        .collect()
+}
+
+#[cfg(all(test, feature = "memory"))]
+mod tests {
+    use super::*;
+    use crate::memory::MemoryRuntime;
+
+    #[test]
+    fn every_member_proof_folds_to_the_root() {
+        let rt = MemoryRuntime::new();
+        let accounts = [11, 22, 33, 44, 55];
+        let root = merkle_root(&rt, &accounts);
+
+        for account in accounts {
+            let proof = merkle_proof(&rt, &accounts, account).unwrap();
+            let node = proof.iter().fold(allowlist_leaf(&rt, account), |node, s| {
+                merkle_parent(&rt, node, *s)
+            });
+            assert_eq!(node, root);
+        }
+        assert!(merkle_proof(&rt, &accounts, 66).is_none());
+    }
+}
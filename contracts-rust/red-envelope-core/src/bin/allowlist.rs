@@ -0,0 +1,134 @@
+//! Builds allowlist Merkle roots and per-account proofs for `setPoolAllowlist`/`proveAllowlist`.
+//!
+//! Accounts may be N3 addresses, `0x` script hashes or the contract's `i64` account ids; the
+//! first two are converted with [`account_id`]. Hashing goes through `MemoryRuntime`, whose
+//! SHA-256 matches CryptoLib, so the printed root is exactly what the contract folds proofs into.
+//!
+//! ```bash
+//! cargo run --release --bin allowlist -- --accounts 11,22,33,44
+//! cargo run --release --bin allowlist -- --file members.txt --prove NepwUjd9GhqgNkrfXaxj9mmsFhFzGoFuWM
+//! ```
+
+use std::env;
+use std::fs;
+use std::process;
+
+use red_envelope_core::{
+    account_id, merkle_proof, merkle_root, script_hash_from_address, script_hash_from_hex,
+    MemoryRuntime,
+};
+
+const USAGE: &str = "\
+usage: allowlist (--accounts A,B,... | --file PATH) [--prove ACCOUNT] [--json]
+
+  --accounts A,B,...  allowlisted accounts, in tree order
+  --file PATH         read accounts from PATH, one per line (blank lines and `#` comments skipped)
+  --prove ACCOUNT     also print the siblings ACCOUNT passes to `proveAllowlist`, in call order
+  --json              print JSON instead of text
+
+An account is an N3 address, a 0x-prefixed script hash or an i64 account id.
+";
+
+struct Options {
+    accounts: Vec<i64>,
+    prove: Option<i64>,
+    json: bool,
+}
+
+fn main() {
+    let options = parse_args().unwrap_or_else(|err| {
+        eprintln!("error: {err}\n\n{USAGE}");
+        process::exit(2);
+    });
+
+    let runtime = MemoryRuntime::new();
+    let root = merkle_root(&runtime, &options.accounts);
+    let proof = options.prove.map(|account| {
+        merkle_proof(&runtime, &options.accounts, account).unwrap_or_else(|| {
+            eprintln!("error: account {account} is not on the allowlist");
+            process::exit(1);
+        })
+    });
+
+    if options.json {
+        print!("{{\"accounts\":{},\"root\":{root}", options.accounts.len());
+        if let Some(proof) = &proof {
+            let siblings: Vec<String> = proof.iter().map(i64::to_string).collect();
+            print!(",\"proof\":[{}]", siblings.join(","));
+        }
+        println!("}}");
+        return;
+    }
+
+    println!("accounts  {}", options.accounts.len());
+    println!("root      {root}");
+    if let Some(proof) = proof {
+        println!("proof     {} sibling(s)", proof.len());
+        for sibling in proof {
+            println!("  {sibling}");
+        }
+    }
+}
+
+fn parse_args() -> Result<Options, String> {
+    let mut options = Options {
+        accounts: Vec::new(),
+        prove: None,
+        json: false,
+    };
+
+    let mut args = env::args().skip(1);
+    while let Some(flag) = args.next() {
+        if flag == "--json" {
+            options.json = true;
+            continue;
+        }
+        if flag == "-h" || flag == "--help" {
+            print!("{USAGE}");
+            process::exit(0);
+        }
+
+        let value = args
+            .next()
+            .ok_or_else(|| format!("missing value for {flag}"))?;
+        match flag.as_str() {
+            "--accounts" => options.accounts.extend(parse_ids(&flag, value.split(','))?),
+            "--file" => {
+                let text = fs::read_to_string(&value)
+                    .map_err(|err| format!("cannot read {value}: {err}"))?;
+                let lines = text
+                    .lines()
+                    .map(str::trim)
+                    .filter(|line| !line.is_empty() && !line.starts_with('#'));
+                options.accounts.extend(parse_ids(&flag, lines)?);
+            }
+            "--prove" => options.prove = Some(parse_ids(&flag, [value.as_str()])?[0]),
+            _ => return Err(format!("unknown option {flag}")),
+        }
+    }
+
+    if options.accounts.is_empty() {
+        return Err("no accounts given".into());
+    }
+    Ok(options)
+}
+
+fn parse_ids<'a>(
+    flag: &str,
+    values: impl IntoIterator<Item = &'a str>,
+) -> Result<Vec<i64>, String> {
+    values
+        .into_iter()
+        .map(|v| parse_account(v.trim()).ok_or_else(|| format!("invalid {flag}: {v}")))
+        .collect()
+}
+
+fn parse_account(value: &str) -> Option<i64> {
+    if value.starts_with("0x") {
+        return script_hash_from_hex(value).map(|hash| account_id(&hash));
+    }
+    value
+        .parse::<i64>()
+        .ok()
+        .or_else(|| script_hash_from_address(value).map(|hash| account_id(&hash)))
+}
@@ -0,0 +1,338 @@
+//! Replays the on-chain packet distribution (`calc_packet`) off-chain with a seeded RNG.
+//!
+//! Rolls go through `MemoryRuntime`, whose SHA-256 matches CryptoLib, so a seed replays
+//! exactly what the contract would pay for the same per-packet entropy.
+//!
+//! ```bash
+//! cargo run --release --bin simulate -- --envelopes 10000 --total 10 --packets 10 --neo 0,100,1000
+//! cargo run --release --bin simulate -- --packets 30 --medium-band 6000,15000 --json
+//! cargo run --release --bin simulate -- --csv packets.csv
+//! ```
+
+use std::env;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::process;
+
+use red_envelope_core::constants::*;
+use red_envelope_core::keys::{k2, C_VALUE};
+use red_envelope_core::{calc_constant, calc_packet, MemoryRuntime, Storage};
+
+const GAS_DECIMALS: f64 = 100_000_000.0;
+const PERCENTILES: [f64; 6] = [0.10, 0.25, 0.50, 0.75, 0.90, 0.99];
+
+const USAGE: &str = "\
+usage: simulate [options]
+
+  --envelopes N         envelopes to simulate (default 10000)
+  --total GAS           GAS per envelope (default 10)
+  --packets N           packets per envelope (default 10)
+  --neo A,B,...         NEO balance per claimer, cycled across packets (default 0)
+  --seed N              RNG seed; identical seeds replay identical runs (default 1)
+  --min-per-packet GAS  override MIN_PER_PACKET
+  --dense-band LO,HI    override DENSE_VOLATILITY_{LOW,HIGH}_BPS
+  --medium-band LO,HI   override MEDIUM_VOLATILITY_{LOW,HIGH}_BPS
+  --sparse-band LO,HI   override SPARSE_VOLATILITY_{LOW,HIGH}_BPS
+  --csv PATH            write every packet as `envelope,packet,neo,amount`
+  --json                print the summary as JSON instead of text
+";
+
+struct Options {
+    runtime: MemoryRuntime,
+    envelopes: i64,
+    total: i64,
+    packets: i64,
+    neo_profile: Vec<i64>,
+    seed: u64,
+    csv: Option<String>,
+    json: bool,
+}
+
+/// SplitMix64: tiny, dependency-free and fully determined by its seed.
+struct SplitMix64(u64);
+
+impl SplitMix64 {
+    fn next_seed(&mut self) -> i64 {
+        self.0 = self.0.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        ((z ^ (z >> 31)) & 0x7fff_ffff_ffff_ffff) as i64
+    }
+}
+
+struct Summary {
+    count: usize,
+    min: i64,
+    max: i64,
+    mean: f64,
+    stddev: f64,
+    percentiles: Vec<(f64, i64)>,
+    luckiest_share_mean: f64,
+    luckiest_share_max: f64,
+}
+
+fn main() {
+    let options = parse_args().unwrap_or_else(|err| {
+        eprintln!("error: {err}\n\n{USAGE}");
+        process::exit(2);
+    });
+
+    let mut csv = options.csv.as_ref().map(|path| {
+        let file = File::create(path).unwrap_or_else(|err| {
+            eprintln!("error: cannot create {path}: {err}");
+            process::exit(1);
+        });
+        let mut out = BufWriter::new(file);
+        writeln!(out, "envelope,packet,neo,amount").expect("csv write");
+        out
+    });
+
+    let mut rng = SplitMix64(options.seed);
+    let mut amounts = Vec::with_capacity((options.envelopes * options.packets) as usize);
+    let mut luckiest_shares = Vec::with_capacity(options.envelopes as usize);
+
+    for envelope in 0..options.envelopes {
+        let mut remaining = options.total;
+        let mut luckiest = 0;
+        for packet in 0..options.packets {
+            let neo = options.neo_profile[packet as usize % options.neo_profile.len()];
+            let amount = calc_packet(
+                &options.runtime,
+                0,
+                remaining,
+                options.packets - packet,
+                neo,
+                options.total,
+                options.packets,
+                rng.next_seed(),
+            );
+            remaining -= amount;
+            luckiest = luckiest.max(amount);
+            amounts.push(amount);
+
+            if let Some(out) = csv.as_mut() {
+                writeln!(out, "{envelope},{packet},{neo},{amount}").expect("csv write");
+            }
+        }
+        luckiest_shares.push(luckiest as f64 / options.total as f64);
+    }
+
+    if let Some(mut out) = csv {
+        out.flush().expect("csv flush");
+    }
+
+    let summary = summarize(amounts, &luckiest_shares);
+    if options.json {
+        print_json(&options, &summary);
+    } else {
+        print_text(&options, &summary);
+    }
+}
+
+fn parse_args() -> Result<Options, String> {
+    let mut options = Options {
+        runtime: MemoryRuntime::new(),
+        envelopes: 10_000,
+        total: 10 * GAS_DECIMALS as i64,
+        packets: 10,
+        neo_profile: vec![0],
+        seed: 1,
+        csv: None,
+        json: false,
+    };
+
+    let mut args = env::args().skip(1);
+    while let Some(flag) = args.next() {
+        if flag == "--json" {
+            options.json = true;
+            continue;
+        }
+        if flag == "-h" || flag == "--help" {
+            print!("{USAGE}");
+            process::exit(0);
+        }
+
+        let value = args
+            .next()
+            .ok_or_else(|| format!("missing value for {flag}"))?;
+        match flag.as_str() {
+            "--envelopes" => options.envelopes = parse_positive(&flag, &value)?,
+            "--total" => options.total = parse_gas(&flag, &value)?,
+            "--packets" => options.packets = parse_positive(&flag, &value)?,
+            "--neo" => {
+                options.neo_profile = value
+                    .split(',')
+                    .map(|v| v.trim().parse::<i64>())
+                    .collect::<Result<_, _>>()
+                    .map_err(|_| format!("invalid {flag}: {value}"))?;
+            }
+            "--seed" => {
+                options.seed = value
+                    .parse()
+                    .map_err(|_| format!("invalid {flag}: {value}"))?;
+            }
+            "--min-per-packet" => {
+                let min_per_packet = parse_gas(&flag, &value)?;
+                options
+                    .runtime
+                    .put(k2(C_VALUE, CONST_MIN_PER_PACKET), min_per_packet);
+            }
+            "--dense-band" => set_band(
+                &mut options.runtime,
+                &flag,
+                &value,
+                CONST_DENSE_LOW_BPS,
+                CONST_DENSE_HIGH_BPS,
+            )?,
+            "--medium-band" => set_band(
+                &mut options.runtime,
+                &flag,
+                &value,
+                CONST_MEDIUM_LOW_BPS,
+                CONST_MEDIUM_HIGH_BPS,
+            )?,
+            "--sparse-band" => set_band(
+                &mut options.runtime,
+                &flag,
+                &value,
+                CONST_SPARSE_LOW_BPS,
+                CONST_SPARSE_HIGH_BPS,
+            )?,
+            "--csv" => options.csv = Some(value),
+            _ => return Err(format!("unknown option {flag}")),
+        }
+    }
+
+    let min_per_packet = calc_constant(&options.runtime, CONST_MIN_PER_PACKET);
+    if options.total < options.packets.saturating_mul(min_per_packet) {
+        return Err(format!(
+            "--total must cover {} packets of at least {} GAS",
+            options.packets,
+            min_per_packet as f64 / GAS_DECIMALS
+        ));
+    }
+    Ok(options)
+}
+
+fn parse_positive(flag: &str, value: &str) -> Result<i64, String> {
+    match value.parse::<i64>() {
+        Ok(v) if v > 0 => Ok(v),
+        _ => Err(format!("invalid {flag}: {value}")),
+    }
+}
+
+fn parse_gas(flag: &str, value: &str) -> Result<i64, String> {
+    match value.parse::<f64>() {
+        Ok(v) if v > 0.0 => Ok((v * GAS_DECIMALS).round() as i64),
+        _ => Err(format!("invalid {flag}: {value}")),
+    }
+}
+
+// Writes straight to the live constant slots, bypassing the contract's admin timelock.
+fn set_band(
+    runtime: &mut MemoryRuntime,
+    flag: &str,
+    value: &str,
+    low_slot: i64,
+    high_slot: i64,
+) -> Result<(), String> {
+    let (low, high) = value
+        .split_once(',')
+        .ok_or_else(|| format!("{flag} expects LO,HI"))?;
+    runtime.put(k2(C_VALUE, low_slot), parse_positive(flag, low.trim())?);
+    runtime.put(k2(C_VALUE, high_slot), parse_positive(flag, high.trim())?);
+    Ok(())
+}
+
+fn summarize(mut amounts: Vec<i64>, luckiest_shares: &[f64]) -> Summary {
+    amounts.sort_unstable();
+    let count = amounts.len();
+    let mean = amounts.iter().map(|&a| a as f64).sum::<f64>() / count as f64;
+    let variance = amounts
+        .iter()
+        .map(|&a| (a as f64 - mean).powi(2))
+        .sum::<f64>()
+        / count as f64;
+
+    let percentiles = PERCENTILES
+        .iter()
+        .map(|&p| {
+            let rank = ((count as f64 * p).ceil() as usize).clamp(1, count);
+            (p, amounts[rank - 1])
+        })
+        .collect();
+
+    Summary {
+        count,
+        min: amounts[0],
+        max: amounts[count - 1],
+        mean,
+        stddev: variance.sqrt(),
+        percentiles,
+        luckiest_share_mean: luckiest_shares.iter().sum::<f64>() / luckiest_shares.len() as f64,
+        luckiest_share_max: luckiest_shares.iter().copied().fold(0.0, f64::max),
+    }
+}
+
+fn gas(amount: f64) -> f64 {
+    amount / GAS_DECIMALS
+}
+
+fn print_text(options: &Options, summary: &Summary) {
+    println!(
+        "{} envelopes x {} packets, {} GAS each, NEO profile {:?}, seed {}",
+        options.envelopes,
+        options.packets,
+        gas(options.total as f64),
+        options.neo_profile,
+        options.seed
+    );
+    println!("packets:  {}", summary.count);
+    println!("min:      {:.8} GAS", gas(summary.min as f64));
+    println!("max:      {:.8} GAS", gas(summary.max as f64));
+    println!("mean:     {:.8} GAS", gas(summary.mean));
+    println!("stddev:   {:.8} GAS", gas(summary.stddev));
+    for (p, amount) in &summary.percentiles {
+        println!("p{:<7} {:.8} GAS", p * 100.0, gas(*amount as f64));
+    }
+    println!(
+        "luckiest: {:.2}% of envelope on average, {:.2}% worst case",
+        summary.luckiest_share_mean * 100.0,
+        summary.luckiest_share_max * 100.0
+    );
+}
+
+fn print_json(options: &Options, summary: &Summary) {
+    let percentiles = summary
+        .percentiles
+        .iter()
+        .map(|(p, amount)| format!("\"p{}\":{}", p * 100.0, amount))
+        .collect::<Vec<_>>()
+        .join(",");
+    let neo_profile = options
+        .neo_profile
+        .iter()
+        .map(i64::to_string)
+        .collect::<Vec<_>>()
+        .join(",");
+
+    println!(
+        "{{\"envelopes\":{},\"packets\":{},\"total\":{},\"neoProfile\":[{}],\"seed\":{},\
+         \"count\":{},\"min\":{},\"max\":{},\"mean\":{:.2},\"stddev\":{:.2},\
+         \"percentiles\":{{{}}},\"luckiestShareMean\":{:.6},\"luckiestShareMax\":{:.6}}}",
+        options.envelopes,
+        options.packets,
+        options.total,
+        neo_profile,
+        options.seed,
+        summary.count,
+        summary.min,
+        summary.max,
+        summary.mean,
+        summary.stddev,
+        percentiles,
+        summary.luckiest_share_mean,
+        summary.luckiest_share_max
+    );
+}
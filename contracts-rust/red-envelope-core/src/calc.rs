@@ -0,0 +1,353 @@
+use crate::constants::*;
+use crate::keys::{
+    env, C_VALUE, P_HIGH_BPS, P_LOW_BPS, P_MIN_PER_PACKET, T_MIN_AMOUNT, T_MIN_PER_PACKET,
+};
+use crate::runtime::{Runtime, Storage};
+
+pub fn calc_constant<S: Storage + ?Sized>(s: &S, slot: i64) -> i64 {
+    let v = env(s, C_VALUE, slot);
+    if v > 0 {
+        v
+    } else {
+        default_calc_constant(slot)
+    }
+}
+
+pub fn default_calc_constant(slot: i64) -> i64 {
+    match slot {
+        CONST_MIN_AMOUNT => MIN_AMOUNT,
+        CONST_MAX_PACKETS => MAX_PACKETS,
+        CONST_MIN_PER_PACKET => MIN_PER_PACKET,
+        CONST_DENSE_LOW_BPS => DENSE_VOLATILITY_LOW_BPS,
+        CONST_DENSE_HIGH_BPS => DENSE_VOLATILITY_HIGH_BPS,
+        CONST_MEDIUM_LOW_BPS => MEDIUM_VOLATILITY_LOW_BPS,
+        CONST_MEDIUM_HIGH_BPS => MEDIUM_VOLATILITY_HIGH_BPS,
+        CONST_SPARSE_LOW_BPS => SPARSE_VOLATILITY_LOW_BPS,
+        CONST_SPARSE_HIGH_BPS => SPARSE_VOLATILITY_HIGH_BPS,
+        CONST_MAX_EXPIRY_MS => MAX_EXPIRY_MS,
+        _ => 0,
+    }
+}
+
+/// GAS (`0`) or a token the owner has whitelisted with `setTokenConfig`.
+pub fn token_accepted<S: Storage + ?Sized>(s: &S, pay_token: i64) -> bool {
+    pay_token == 0 || env(s, T_MIN_AMOUNT, pay_token) > 0
+}
+
+/// Per-token replacement for `CONST_MIN_AMOUNT`; GAS uses the calculation constant.
+pub fn token_min_amount<S: Storage + ?Sized>(s: &S, pay_token: i64) -> i64 {
+    let v = env(s, T_MIN_AMOUNT, pay_token);
+    if pay_token != 0 && v > 0 {
+        v
+    } else {
+        calc_constant(s, CONST_MIN_AMOUNT)
+    }
+}
+
+/// Per-token replacement for `CONST_MIN_PER_PACKET`; kept after delisting so live envelopes still settle.
+pub fn token_min_per_packet<S: Storage + ?Sized>(s: &S, pay_token: i64) -> i64 {
+    let v = env(s, T_MIN_PER_PACKET, pay_token);
+    if pay_token != 0 && v > 0 {
+        v
+    } else {
+        calc_constant(s, CONST_MIN_PER_PACKET)
+    }
+}
+
+/// Distribution inputs an envelope snapshots at creation, so retuned constants only reach new envelopes.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PacketTerms {
+    pub min_per_packet: i64,
+    pub low_bps: i64,
+    pub high_bps: i64,
+}
+
+/// Terms a new `pay_token` envelope with `total_packets` packets would get right now.
+pub fn packet_terms<S: Storage + ?Sized>(s: &S, pay_token: i64, total_packets: i64) -> PacketTerms {
+    PacketTerms {
+        min_per_packet: token_min_per_packet(s, pay_token),
+        low_bps: volatility_lower_bps(s, total_packets),
+        high_bps: volatility_upper_bps(s, total_packets),
+    }
+}
+
+/// Terms `envelope_id` was created with; envelopes that predate snapshots use live values.
+pub fn envelope_packet_terms<S: Storage + ?Sized>(
+    s: &S,
+    envelope_id: i64,
+    pay_token: i64,
+    total_packets: i64,
+) -> PacketTerms {
+    let min_per_packet = env(s, P_MIN_PER_PACKET, envelope_id);
+    if min_per_packet <= 0 {
+        return packet_terms(s, pay_token, total_packets);
+    }
+    PacketTerms {
+        min_per_packet,
+        low_bps: env(s, P_LOW_BPS, envelope_id),
+        high_bps: env(s, P_HIGH_BPS, envelope_id),
+    }
+}
+
+pub fn volatility_band_valid(low_bps: i64, high_bps: i64) -> bool {
+    (1..=PERCENT_BASE).contains(&low_bps) && (PERCENT_BASE..=MAX_VOLATILITY_BPS).contains(&high_bps)
+}
+
+/// [`calc_packet_with_terms`] under the live constants for `pay_token`.
+#[allow(clippy::too_many_arguments)]
+pub fn calc_packet<R: Runtime + ?Sized>(
+    rt: &R,
+    pay_token: i64,
+    remaining_amount: i64,
+    packets_left: i64,
+    neo_balance: i64,
+    total_amount: i64,
+    total_packets: i64,
+    seed: i64,
+) -> i64 {
+    calc_packet_with_terms(
+        rt,
+        packet_terms(rt, pay_token, total_packets),
+        remaining_amount,
+        packets_left,
+        neo_balance,
+        total_amount,
+        total_packets,
+        seed,
+    )
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn calc_packet_with_terms<R: Runtime + ?Sized>(
+    rt: &R,
+    terms: PacketTerms,
+    remaining_amount: i64,
+    packets_left: i64,
+    neo_balance: i64,
+    total_amount: i64,
+    total_packets: i64,
+    seed: i64,
+) -> i64 {
+    if remaining_amount <= 0 || packets_left <= 0 || total_amount <= 0 || total_packets <= 0 {
+        return 0;
+    }
+
+    if packets_left == 1 {
+        return remaining_amount;
+    }
+
+    let min_per_packet = terms.min_per_packet;
+    let feasible_max =
+        remaining_amount.saturating_sub((packets_left - 1).saturating_mul(min_per_packet));
+    if feasible_max <= min_per_packet {
+        return min_per_packet;
+    }
+
+    let dynamic_average = ceiling_div(remaining_amount, packets_left);
+    let lower_band_bps = terms.low_bps;
+    let upper_band_bps = terms.high_bps;
+
+    let mut min_for_this = dynamic_average.saturating_mul(lower_band_bps) / PERCENT_BASE;
+    if min_for_this < min_per_packet {
+        min_for_this = min_per_packet;
+    }
+
+    let mut max_for_this =
+        ceiling_div(dynamic_average.saturating_mul(upper_band_bps), PERCENT_BASE);
+
+    let cap_by_percent = ceiling_div(
+        total_amount.saturating_mul(MAX_SINGLE_PACKET_BPS),
+        PERCENT_BASE,
+    );
+    let cap_by_average = ceiling_div(
+        dynamic_average.saturating_mul(MAX_SINGLE_PACKET_AVG_BPS),
+        PERCENT_BASE,
+    );
+    let mut hard_cap = cap_by_percent.max(cap_by_average);
+    if hard_cap < min_per_packet {
+        hard_cap = min_per_packet;
+    }
+
+    if max_for_this > hard_cap {
+        max_for_this = hard_cap;
+    }
+    if max_for_this > feasible_max {
+        max_for_this = feasible_max;
+    }
+
+    if min_for_this > max_for_this {
+        min_for_this = min_per_packet;
+        max_for_this = feasible_max;
+    }
+
+    let range = max_for_this.saturating_sub(min_for_this).saturating_add(1);
+    if range <= 0 {
+        return min_per_packet.min(feasible_max);
+    }
+
+    // Every roll hashes (seed, counter) so later trials never run out of entropy bits.
+    let mut counter = 0;
+    let roll1 = entropy_draw(rt, seed, counter, range);
+    counter += 1;
+    let roll2 = entropy_draw(rt, seed, counter, range);
+    counter += 1;
+    let mut best_roll = roll1.saturating_add(roll2) / 2;
+
+    let mut extra_trials = 0;
+    if neo_balance >= 1000 {
+        extra_trials = 2;
+    } else if neo_balance >= 100 {
+        extra_trials = 1;
+    }
+
+    let mut i = 0;
+    while i < extra_trials {
+        let candidate1 = entropy_draw(rt, seed, counter, range);
+        counter += 1;
+        let candidate2 = entropy_draw(rt, seed, counter, range);
+        counter += 1;
+
+        let candidate_roll = candidate1.saturating_add(candidate2) / 2;
+        if candidate_roll > best_roll {
+            best_roll = candidate_roll;
+        }
+        i += 1;
+    }
+
+    let mut amount = min_for_this.saturating_add(best_roll);
+    if amount < min_per_packet {
+        amount = min_per_packet;
+    }
+    if amount > feasible_max {
+        amount = feasible_max;
+    }
+    amount
+}
+
+/// Fixed share for equal-split envelopes; the last packet takes whatever the division left over.
+pub fn equal_packet(
+    remaining_amount: i64,
+    packets_left: i64,
+    total_amount: i64,
+    total_packets: i64,
+) -> i64 {
+    if remaining_amount <= 0 || packets_left <= 0 || total_amount <= 0 || total_packets <= 0 {
+        return 0;
+    }
+
+    if packets_left == 1 {
+        return remaining_amount;
+    }
+
+    (total_amount / total_packets).min(remaining_amount)
+}
+
+fn entropy_draw<R: Runtime + ?Sized>(rt: &R, seed: i64, counter: i64, range: i64) -> i64 {
+    let bits = rt.sha256_pair(counter, seed);
+    (bits & i64::MAX).rem_euclid(range)
+}
+
+fn ceiling_div(numerator: i64, denominator: i64) -> i64 {
+    if denominator <= 0 || numerator <= 0 {
+        return 0;
+    }
+    numerator.saturating_add(denominator.saturating_sub(1)) / denominator
+}
+
+fn volatility_lower_bps<S: Storage + ?Sized>(s: &S, total_packets: i64) -> i64 {
+    if total_packets >= DENSE_PACKET_THRESHOLD {
+        calc_constant(s, CONST_DENSE_LOW_BPS)
+    } else if total_packets >= MEDIUM_PACKET_THRESHOLD {
+        calc_constant(s, CONST_MEDIUM_LOW_BPS)
+    } else {
+        calc_constant(s, CONST_SPARSE_LOW_BPS)
+    }
+}
+
+fn volatility_upper_bps<S: Storage + ?Sized>(s: &S, total_packets: i64) -> i64 {
+    if total_packets >= DENSE_PACKET_THRESHOLD {
+        calc_constant(s, CONST_DENSE_HIGH_BPS)
+    } else if total_packets >= MEDIUM_PACKET_THRESHOLD {
+        calc_constant(s, CONST_MEDIUM_HIGH_BPS)
+    } else {
+        calc_constant(s, CONST_SPARSE_HIGH_BPS)
+    }
+}
+
+#[cfg(all(test, feature = "memory"))]
+mod tests {
+    use super::*;
+    use crate::memory::MemoryRuntime;
+
+    const SAMPLES: i64 = 4_000;
+
+    fn mean_first_packet(total: i64, packets: i64, neo_balance: i64) -> i64 {
+        let rt = MemoryRuntime::new();
+        let mut sum = 0i64;
+        let mut seed = 1;
+        while seed <= SAMPLES {
+            sum += calc_packet(&rt, 0, total, packets, neo_balance, total, packets, seed);
+            seed += 1;
+        }
+        sum / SAMPLES
+    }
+
+    #[test]
+    fn neo_boost_shifts_mean_up() {
+        let total = 10 * MIN_AMOUNT;
+        let base = mean_first_packet(total, 10, 0);
+        let boosted = mean_first_packet(total, 10, 100);
+        let max_boost = mean_first_packet(total, 10, 1_000);
+
+        // One extra best-of-two trial lifts the mean by several percent of the average packet.
+        assert!(
+            boosted > base + total / 10 / 50,
+            "base={base} boosted={boosted}"
+        );
+        assert!(max_boost > boosted, "boosted={boosted} max={max_boost}");
+    }
+
+    #[test]
+    fn neo_boost_survives_ranges_that_used_to_saturate() {
+        // range^2 overflows i64 here, which zeroed every extra trial under divisor slicing.
+        let total = 10_000 * MIN_AMOUNT;
+        let base = mean_first_packet(total, 2, 0);
+        let max_boost = mean_first_packet(total, 2, 1_000);
+        assert!(
+            max_boost > base + total / 2 / 50,
+            "base={base} max={max_boost}"
+        );
+    }
+
+    #[test]
+    fn equal_split_gives_remainder_to_last_packet() {
+        let total = 10 * MIN_AMOUNT + 2;
+        let packets = 3;
+        let mut remaining = total;
+        let mut left = packets;
+        let mut amounts = [0i64; 3];
+        for amount in amounts.iter_mut() {
+            *amount = equal_packet(remaining, left, total, packets);
+            remaining -= *amount;
+            left -= 1;
+        }
+        assert_eq!(amounts[0], total / packets);
+        assert_eq!(amounts[1], total / packets);
+        assert_eq!(amounts[2], total / packets + total % packets);
+        assert_eq!(remaining, 0);
+    }
+
+    #[test]
+    fn rolls_stay_within_feasible_bounds() {
+        let total = 5 * MIN_AMOUNT;
+        let packets = 7;
+        let rt = MemoryRuntime::new();
+        let mut seed = 1;
+        while seed <= SAMPLES {
+            let amount = calc_packet(&rt, 0, total, packets, 1_000, total, packets, seed);
+            assert!(amount >= MIN_PER_PACKET);
+            assert!(amount <= total - (packets - 1) * MIN_PER_PACKET);
+            seed += 1;
+        }
+    }
+}
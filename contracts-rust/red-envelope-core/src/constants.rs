@@ -0,0 +1,103 @@
+pub const MIN_AMOUNT: i64 = 100_000_000;
+pub const MAX_PACKETS: i64 = 100;
+pub const MIN_PER_PACKET: i64 = 10_000_000;
+pub const PERCENT_BASE: i64 = 10_000;
+pub const MAX_SINGLE_PACKET_BPS: i64 = 2_000;
+pub const MAX_SINGLE_PACKET_AVG_BPS: i64 = 18_000;
+pub const DENSE_PACKET_THRESHOLD: i64 = 50;
+pub const MEDIUM_PACKET_THRESHOLD: i64 = 20;
+pub const DENSE_VOLATILITY_LOW_BPS: i64 = 7_000;
+pub const DENSE_VOLATILITY_HIGH_BPS: i64 = 13_000;
+pub const MEDIUM_VOLATILITY_LOW_BPS: i64 = 5_000;
+pub const MEDIUM_VOLATILITY_HIGH_BPS: i64 = 17_000;
+pub const SPARSE_VOLATILITY_LOW_BPS: i64 = 3_000;
+pub const SPARSE_VOLATILITY_HIGH_BPS: i64 = 23_000;
+pub const COMMIT_REVEAL_WINDOW_MS: i64 = 3_600_000;
+pub const DEFAULT_EXPIRY_MS: i64 = 604_800_000;
+pub const MAX_EXPIRY_MS: i64 = 604_800_000;
+pub const MAX_PACKETS_UPPER_BOUND: i64 = 500;
+pub const MAX_VOLATILITY_BPS: i64 = 50_000;
+// Adapter payloads carry expiry as `expiryMs * 1_000` below the type marker, so stay under 1e9 ms.
+pub const MAX_EXPIRY_UPPER_BOUND_MS: i64 = 864_000_000;
+
+pub const CONST_MIN_AMOUNT: i64 = 1;
+pub const CONST_MAX_PACKETS: i64 = 2;
+pub const CONST_MIN_PER_PACKET: i64 = 3;
+pub const CONST_DENSE_LOW_BPS: i64 = 4;
+pub const CONST_DENSE_HIGH_BPS: i64 = 5;
+pub const CONST_MEDIUM_LOW_BPS: i64 = 6;
+pub const CONST_MEDIUM_HIGH_BPS: i64 = 7;
+pub const CONST_SPARSE_LOW_BPS: i64 = 8;
+pub const CONST_SPARSE_HIGH_BPS: i64 = 9;
+pub const CONST_MAX_EXPIRY_MS: i64 = 10;
+pub const CONST_COUNT: i64 = 10;
+
+/// GAS script hash 0xd2a4cff31913016155e38e474a2c06d08be276cf as an integer account value (its
+/// first eight little-endian bytes). Envelopes store GAS as pay token `0`.
+pub const GAS_TOKEN: i64 = i64::from_le_bytes([0xcf, 0x76, 0xe2, 0x8b, 0xd0, 0x06, 0x2c, 0x4a]);
+
+pub const ENVELOPE_TYPE_SPREADING: i64 = 0;
+pub const ENVELOPE_TYPE_POOL: i64 = 1;
+pub const ENVELOPE_TYPE_CLAIM: i64 = 2;
+/// Pool-style envelope paying `total / packets` per claim, remainder to the last claim.
+pub const ENVELOPE_TYPE_EQUAL: i64 = 3;
+pub const ON_NEP17_ADAPTER_BASE: i64 = 1_000_000_000_000;
+pub const ON_NEP17_ADAPTER_TYPE_MULTIPLIER: i64 = 1_000_000_000_000;
+pub const ON_NEP17_ADAPTER_EXPIRY_MULTIPLIER: i64 = 1_000;
+pub const ON_NEP17_ADAPTER_FLAGS_MULTIPLIER: i64 = 10_000_000_000_000;
+/// Start delay rides above a two-digit flags field, in minutes; `i64` caps it at 9_223 minutes
+/// (about 6.4 days).
+pub const ON_NEP17_ADAPTER_START_MULTIPLIER: i64 = 1_000_000_000_000_000;
+pub const ON_NEP17_ADAPTER_MINUTE_MS: i64 = 60_000;
+// Top-ups are negative, far below the legacy sign-based payloads:
+// -(TOPUP_BASE + envelopeId + addPackets * TOPUP_PACKETS_MULTIPLIER + extendMinutes * TOPUP_EXTEND_MULTIPLIER)
+pub const ON_NEP17_TOPUP_BASE: i64 = 100_000_000_000_000_000;
+pub const ON_NEP17_TOPUP_PACKETS_MULTIPLIER: i64 = 1_000_000_000;
+pub const ON_NEP17_TOPUP_EXTEND_MULTIPLIER: i64 = 1_000_000_000_000;
+// Batches sit between zero and the legacy sign-based payloads:
+// -(BATCH_BASE + packetCount + type * BATCH_TYPE_MULTIPLIER + count * BATCH_COUNT_MULTIPLIER
+//   + expiryMinutes * BATCH_EXPIRY_MULTIPLIER)
+pub const ON_NEP17_BATCH_BASE: i64 = 100_000_000_000;
+pub const ON_NEP17_BATCH_TYPE_MULTIPLIER: i64 = 1_000;
+pub const ON_NEP17_BATCH_COUNT_MULTIPLIER: i64 = 10_000;
+pub const ON_NEP17_BATCH_EXPIRY_MULTIPLIER: i64 = 1_000_000;
+/// Envelopes per batch deposit, bounding the GAS one `onNEP17Payment` can burn.
+pub const MAX_BATCH_ENVELOPES: i64 = 50;
+/// Entries one paged index getter returns, bounding the GAS of a read.
+pub const MAX_PAGE_SIZE: i64 = 50;
+pub const ON_NEP17_LEGACY_PACK_BASE: i64 = 10;
+
+pub const ENVELOPE_FLAG_COMMIT_REVEAL: i64 = 1;
+/// Pool only pays claimers presenting the code behind its `setPoolSecret` commitment.
+pub const ENVELOPE_FLAG_SECRET: i64 = 2;
+/// Pool only pays claimers whose folded Merkle proof matches its `setPoolAllowlist` root.
+pub const ENVELOPE_FLAG_ALLOWLIST: i64 = 4;
+/// Spreading NFT stays in escrow until the creator names a recipient with `setEnvelopeRecipient`.
+pub const ENVELOPE_FLAG_TARGETED: i64 = 8;
+/// Targeted NFT cannot be transferred away from its recipient.
+pub const ENVELOPE_FLAG_RECIPIENT_ONLY: i64 = 16;
+pub const ENVELOPE_FLAGS_KNOWN: i64 = ENVELOPE_FLAG_COMMIT_REVEAL
+    | ENVELOPE_FLAG_SECRET
+    | ENVELOPE_FLAG_ALLOWLIST
+    | ENVELOPE_FLAG_TARGETED
+    | ENVELOPE_FLAG_RECIPIENT_ONLY;
+
+pub const COMMIT_PENDING: i64 = 1;
+pub const COMMIT_REVEALED: i64 = 2;
+pub const COMMIT_EXPIRED: i64 = 3;
+
+pub const ELIGIBILITY_OK: i64 = 0;
+pub const E_NOT_FOUND: i64 = 1;
+pub const E_NOT_ACTIVE: i64 = 2;
+pub const E_EXPIRED: i64 = 3;
+pub const E_DEPLETED: i64 = 4;
+pub const E_ALREADY_OPENED: i64 = 5;
+pub const E_ALREADY_CLAIMED: i64 = 6;
+pub const E_NOT_HOLDER: i64 = 7;
+pub const E_INVALID_TYPE: i64 = 10;
+pub const E_FROZEN: i64 = 11;
+pub const E_CONTRACT_ACCOUNT: i64 = 12;
+pub const E_COMMIT_PENDING: i64 = 13;
+pub const E_WRONG_CODE: i64 = 14;
+pub const E_NOT_ALLOWLISTED: i64 = 15;
+pub const E_NOT_STARTED: i64 = 16;
@@ -0,0 +1,112 @@
+use crate::allowlist::allowlist_proven;
+use crate::constants::*;
+use crate::keys::*;
+use crate::runtime::Runtime;
+
+/// Returns `ELIGIBILITY_OK` or the first `E_*` reason `user` cannot act on `envelope_id`.
+///
+/// Without `include_action_checks` only existence, freeze and activity are checked. `now_ms` is the
+/// caller's effective clock, so a testnet time override applies here as well.
+pub fn eligibility_status<R: Runtime + ?Sized>(
+    rt: &R,
+    envelope_id: i64,
+    user: i64,
+    include_action_checks: bool,
+    now_ms: i64,
+) -> i64 {
+    if !exists(rt, envelope_id) {
+        return E_NOT_FOUND;
+    }
+
+    if is_frozen(rt, envelope_id) {
+        return E_FROZEN;
+    }
+
+    if env(rt, P_ACTIVE, envelope_id) == 0 {
+        return E_NOT_ACTIVE;
+    }
+
+    if include_action_checks {
+        if rt.is_contract_account(user) {
+            return E_CONTRACT_ACCOUNT;
+        }
+
+        if now_ms < env(rt, P_START, envelope_id) || awaiting_recipient(rt, envelope_id) {
+            return E_NOT_STARTED;
+        }
+
+        if now_ms > env(rt, P_EXPIRY, envelope_id) {
+            return E_EXPIRED;
+        }
+
+        if env(rt, P_OPENED, envelope_id) >= env(rt, P_PACKET, envelope_id)
+            || env(rt, P_REMAINING, envelope_id) <= 0
+        {
+            return E_DEPLETED;
+        }
+
+        let t = env(rt, P_TYPE, envelope_id);
+        if t == ENVELOPE_TYPE_POOL || t == ENVELOPE_TYPE_EQUAL {
+            if rt.get(k3(P_POOL_CLAIMED, envelope_id, user)) > 0 {
+                return E_ALREADY_CLAIMED;
+            }
+
+            let commit_state = rt.get(k3(P_COMMIT_STATE, envelope_id, user));
+            if commit_state == COMMIT_PENDING {
+                return E_COMMIT_PENDING;
+            }
+            if commit_state != 0 {
+                return E_ALREADY_CLAIMED;
+            }
+
+            if !allowlist_proven(rt, envelope_id, user) {
+                return E_NOT_ALLOWLISTED;
+            }
+        } else if t == ENVELOPE_TYPE_SPREADING || t == ENVELOPE_TYPE_CLAIM {
+            if rt.get(k2(P_TOKEN_OWNER, envelope_id)) != user {
+                return E_NOT_HOLDER;
+            }
+
+            if t == ENVELOPE_TYPE_SPREADING && rt.get(k3(P_OPENED_AMOUNT, envelope_id, user)) > 0 {
+                return E_ALREADY_OPENED;
+            }
+
+            if t == ENVELOPE_TYPE_CLAIM && env(rt, P_OPENED, envelope_id) > 0 {
+                return E_ALREADY_OPENED;
+            }
+        } else {
+            return E_INVALID_TYPE;
+        }
+    }
+
+    ELIGIBILITY_OK
+}
+
+/// Commitment a secret pool stores for `code`: SHA-256 over (code, pool id), folded like every
+/// other `sha256_pair` result. Binding the pool id keeps one code's hash from unlocking another pool.
+pub fn secret_commitment<R: Runtime + ?Sized>(rt: &R, pool_id: i64, code: i64) -> i64 {
+    rt.sha256_pair(pool_id, code)
+}
+
+/// [`eligibility_status`] with action checks, plus `E_WRONG_CODE` when a secret pool rejects `code`.
+pub fn code_eligibility_status<R: Runtime + ?Sized>(
+    rt: &R,
+    pool_id: i64,
+    user: i64,
+    code: i64,
+    now_ms: i64,
+) -> i64 {
+    let status = eligibility_status(rt, pool_id, user, true, now_ms);
+    if status != ELIGIBILITY_OK {
+        return status;
+    }
+
+    if env(rt, P_FLAGS, pool_id) & ENVELOPE_FLAG_SECRET != 0 {
+        let commitment = env(rt, P_SECRET_HASH, pool_id);
+        if commitment == 0 || secret_commitment(rt, pool_id, code) != commitment {
+            return E_WRONG_CODE;
+        }
+    }
+
+    ELIGIBILITY_OK
+}
@@ -0,0 +1,120 @@
+//! Read side of the per-creator envelope, per-holder claim and per-pool claim indexes.
+//!
+//! The contract's paged getters return the [`page_range`] of an index as one `Array`; off-chain
+//! tooling reads whole pages with [`page`].
+
+use core::ops::Range;
+
+use crate::keys::{
+    env, k2, k3, I_CREATOR_COUNT, I_CREATOR_ENVELOPE, I_HOLDER_CLAIM, I_HOLDER_COUNT, P_CLAIMER,
+    P_OPENED, P_POOL_CLAIM_INDEX, P_TOTAL,
+};
+use crate::runtime::Storage;
+
+/// One slot of a pool's claim list, as shown on its leaderboard.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct PoolClaimRecord {
+    pub claim_index: i64,
+    pub claim_id: i64,
+    /// 0 for claims minted before claimers were recorded.
+    pub claimer: i64,
+    pub amount: i64,
+    pub opened: bool,
+}
+
+pub fn creator_envelope_count<S: Storage + ?Sized>(s: &S, creator: i64) -> i64 {
+    s.get(k2(I_CREATOR_COUNT, creator))
+}
+
+/// Id of the creator's `index`-th envelope in creation order, or 0 past the end.
+pub fn creator_envelope_at<S: Storage + ?Sized>(s: &S, creator: i64, index: i64) -> i64 {
+    if index < 0 || index >= creator_envelope_count(s, creator) {
+        return 0;
+    }
+    s.get(k3(I_CREATOR_ENVELOPE, creator, index))
+}
+
+pub fn holder_claim_count<S: Storage + ?Sized>(s: &S, holder: i64) -> i64 {
+    s.get(k2(I_HOLDER_COUNT, holder))
+}
+
+/// Id of the `index`-th claim NFT `holder` owns, or 0 past the end. Transfers move the last
+/// entry into the vacated slot, so the order is not stable across transfers.
+pub fn holder_claim_at<S: Storage + ?Sized>(s: &S, holder: i64, index: i64) -> i64 {
+    if index < 0 || index >= holder_claim_count(s, holder) {
+        return 0;
+    }
+    s.get(k3(I_HOLDER_CLAIM, holder, index))
+}
+
+/// Claims minted so far; claim indexes run from 1 to this count.
+pub fn pool_claim_count<S: Storage + ?Sized>(s: &S, pool_id: i64) -> i64 {
+    env(s, P_OPENED, pool_id)
+}
+
+pub fn pool_claim_record<S: Storage + ?Sized>(
+    s: &S,
+    pool_id: i64,
+    claim_index: i64,
+) -> PoolClaimRecord {
+    let claim_id = s.get(k3(P_POOL_CLAIM_INDEX, pool_id, claim_index));
+    if claim_id == 0 {
+        return PoolClaimRecord::default();
+    }
+    PoolClaimRecord {
+        claim_index,
+        claim_id,
+        claimer: env(s, P_CLAIMER, claim_id),
+        amount: env(s, P_TOTAL, claim_id),
+        opened: env(s, P_OPENED, claim_id) != 0,
+    }
+}
+
+/// Indexes `offset..offset + limit` clipped to `0..count`; empty past the end.
+pub fn page_range(count: i64, offset: i64, limit: i64) -> Range<i64> {
+    let start = offset.clamp(0, count.max(0));
+    start..start.saturating_add(limit.max(0)).min(count.max(0))
+}
+
+/// Fills `out` with `at(offset)`, `at(offset + 1)`, ... up to `count`; returns how many were written.
+pub fn page<T>(count: i64, offset: i64, out: &mut [T], at: impl Fn(i64) -> T) -> usize {
+    let mut written = 0;
+    for index in page_range(count, offset, out.len() as i64) {
+        out[written] = at(index);
+        written += 1;
+    }
+    written
+}
+
+#[cfg(all(test, feature = "memory"))]
+mod tests {
+    use super::*;
+    use crate::memory::MemoryRuntime;
+
+    #[test]
+    fn pages_stop_at_the_end_of_the_index() {
+        let mut rt = MemoryRuntime::new();
+        for (i, id) in [5, 9, 12].into_iter().enumerate() {
+            rt.put(k3(I_CREATOR_ENVELOPE, 7, i as i64), id);
+        }
+        rt.put(k2(I_CREATOR_COUNT, 7), 3);
+
+        let mut out = [0; 2];
+        let count = creator_envelope_count(&rt, 7);
+        assert_eq!(
+            page(count, 1, &mut out, |i| creator_envelope_at(&rt, 7, i)),
+            2
+        );
+        assert_eq!(out, [9, 12]);
+        assert_eq!(
+            page(count, 3, &mut out, |i| creator_envelope_at(&rt, 7, i)),
+            0
+        );
+        assert_eq!(creator_envelope_at(&rt, 7, 3), 0);
+
+        assert_eq!(page_range(count, -1, 2), 0..2);
+        assert_eq!(page_range(count, 2, i64::MAX), 2..3);
+        assert_eq!(page_range(count, 5, 2), 3..3);
+        assert_eq!(page_range(count, 0, -1), 0..0);
+    }
+}
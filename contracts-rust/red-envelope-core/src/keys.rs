@@ -0,0 +1,133 @@
+//! Storage layout: key prefixes and the i64 key derivation the contract writes under.
+
+use crate::runtime::Storage;
+
+pub const K_OWNER: u8 = 0x01;
+pub const K_NEXT_ID: u8 = 0x02;
+pub const K_TOTAL_ENVELOPES: u8 = 0x03;
+pub const K_TOTAL_DISTRIBUTED: u8 = 0x04;
+pub const K_TIME_OVERRIDE: u8 = 0x05;
+pub const K_TOTAL_SUPPLY: u8 = 0x06;
+pub const K_PAUSED: u8 = 0x07;
+pub const K_NEXT_ADMIN_OP: u8 = 0x08;
+
+pub const P_CREATOR: u8 = 0x10;
+pub const P_TOTAL: u8 = 0x11;
+pub const P_PACKET: u8 = 0x12;
+pub const P_TYPE: u8 = 0x13;
+pub const P_PARENT: u8 = 0x14;
+pub const P_OPENED: u8 = 0x15;
+pub const P_REMAINING: u8 = 0x16;
+pub const P_ACTIVE: u8 = 0x17;
+pub const P_EXPIRY: u8 = 0x18;
+pub const P_FROZEN: u8 = 0x19;
+pub const P_FLAGS: u8 = 0x1a;
+pub const P_PENDING_COMMITS: u8 = 0x1b;
+pub const P_SECRET_HASH: u8 = 0x1c;
+pub const P_ALLOWLIST_ROOT: u8 = 0x1d;
+pub const P_RECIPIENT: u8 = 0x1e;
+/// NEP-17 asset the envelope is denominated in; `0` is GAS.
+pub const P_PAY_TOKEN: u8 = 0x1f;
+/// Block time opens and claims unlock at; `0` (unset) means immediately.
+pub const P_START: u8 = 0x50;
+/// Account a claim NFT was minted to; `P_TOKEN_OWNER` follows later transfers.
+pub const P_CLAIMER: u8 = 0x51;
+// Best packet so far and who got it; earlier packets win ties.
+pub const P_LUCKIEST_AMOUNT: u8 = 0x52;
+pub const P_LUCKIEST_HOLDER: u8 = 0x53;
+// Distribution terms fixed at creation; `0` (envelopes created before snapshots) reads live values.
+pub const P_MIN_PER_PACKET: u8 = 0x54;
+pub const P_LOW_BPS: u8 = 0x55;
+pub const P_HIGH_BPS: u8 = 0x56;
+
+pub const P_TOKEN_OWNER: u8 = 0x20;
+pub const P_OPENED_AMOUNT: u8 = 0x21;
+pub const P_POOL_CLAIMED: u8 = 0x22;
+pub const P_POOL_CLAIM_INDEX: u8 = 0x23;
+pub const P_OWNER_BALANCE: u8 = 0x24;
+pub const P_COMMIT_STATE: u8 = 0x25;
+pub const P_COMMIT_AT: u8 = 0x26;
+pub const P_ALLOWLIST_NODE: u8 = 0x27;
+// Block holding a pending commit; the next block's nonce seeds its reveal.
+pub const P_COMMIT_BLOCK: u8 = 0x28;
+
+pub const A_KIND: u8 = 0x30;
+pub const A_ARG0: u8 = 0x31;
+pub const A_ARG1: u8 = 0x32;
+pub const A_ETA: u8 = 0x33;
+pub const A_STATE: u8 = 0x34;
+// Folded SHA-256 of a queued upgrade's NEF and manifest, for anyone auditing the op.
+pub const A_NEF_HASH: u8 = 0x35;
+pub const A_MANIFEST_HASH: u8 = 0x36;
+
+pub const C_VALUE: u8 = 0x38;
+pub const C_PENDING: u8 = 0x39;
+
+// Whitelisted NEP-17 assets, keyed by token hash; a zero minimum means not accepted.
+pub const T_MIN_AMOUNT: u8 = 0x40;
+pub const T_MIN_PER_PACKET: u8 = 0x41;
+
+// Per-creator envelope index: entry count, 0-based entries (claims excluded) and the settled
+// prefix `reclaimAllExpired` resumes from.
+pub const I_CREATOR_COUNT: u8 = 0x60;
+pub const I_CREATOR_ENVELOPE: u8 = 0x61;
+pub const I_CREATOR_RECLAIM_CURSOR: u8 = 0x62;
+// Per-holder claim NFT index, swap-removed on transfer; `I_HOLDER_SLOT` is the claim's 1-based
+// position in its holder's list.
+pub const I_HOLDER_COUNT: u8 = 0x63;
+pub const I_HOLDER_CLAIM: u8 = 0x64;
+pub const I_HOLDER_SLOT: u8 = 0x65;
+
+#[inline(always)]
+pub fn key(prefix: u8) -> i64 {
+    prefix as i64
+}
+
+#[inline(always)]
+pub fn mix64(mut x: u64) -> u64 {
+    x ^= x >> 30;
+    x = x.wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    x ^= x >> 27;
+    x = x.wrapping_mul(0x94d0_49bb_1331_11eb);
+    x ^ (x >> 31)
+}
+
+#[inline(always)]
+fn positive_key(x: u64) -> i64 {
+    let v = (x & 0x7fff_ffff_ffff_ffff) as i64;
+    if v == 0 {
+        1
+    } else {
+        v
+    }
+}
+
+#[inline(always)]
+pub fn k2(prefix: u8, a: i64) -> i64 {
+    let seed = ((prefix as u64) << 56) ^ (a as u64);
+    positive_key(mix64(seed))
+}
+
+#[inline(always)]
+pub fn k3(prefix: u8, a: i64, b: i64) -> i64 {
+    let seed = ((prefix as u64) << 56) ^ mix64(a as u64) ^ mix64((b as u64).rotate_left(17));
+    positive_key(mix64(seed))
+}
+
+pub fn env<S: Storage + ?Sized>(s: &S, prefix: u8, id: i64) -> i64 {
+    s.get(k2(prefix, id))
+}
+
+pub fn exists<S: Storage + ?Sized>(s: &S, id: i64) -> bool {
+    env(s, P_CREATOR, id) != 0
+}
+
+pub fn is_frozen<S: Storage + ?Sized>(s: &S, id: i64) -> bool {
+    env(s, P_FROZEN, id) != 0
+}
+
+/// A targeted envelope has not started until its creator names the recipient.
+pub fn awaiting_recipient<S: Storage + ?Sized>(s: &S, id: i64) -> bool {
+    env(s, P_FLAGS, id) & crate::constants::ENVELOPE_FLAG_TARGETED != 0
+        && env(s, P_RECIPIENT, id) == 0
+}
@@ -0,0 +1,44 @@
+//! Chain-independent Red Envelope logic shared by the Neo contract and off-chain tooling.
+//!
+//! Everything here reads chain state through [`Runtime`], so the same code runs inside the
+//! NeoVM contract, in an indexer replaying storage, or in a host-side simulator.
+
+#![no_std]
+
+#[cfg(feature = "memory")]
+extern crate alloc;
+
+pub mod account;
+pub mod adapter;
+pub mod allowlist;
+pub mod calc;
+pub mod constants;
+pub mod eligibility;
+pub mod index;
+pub mod keys;
+#[cfg(feature = "memory")]
+pub mod memory;
+pub mod runtime;
+
+#[cfg(feature = "memory")]
+pub use account::script_hash_from_address;
+pub use account::{account_id, script_hash_from_hex};
+pub use adapter::{
+    decode_batch, decode_payment, decode_top_up, BatchTerms, PaymentTerms, TopUpTerms,
+};
+pub use allowlist::{allowlist_leaf, allowlist_proven, merkle_parent};
+#[cfg(feature = "memory")]
+pub use allowlist::{merkle_proof, merkle_root};
+pub use calc::{
+    calc_constant, calc_packet, calc_packet_with_terms, default_calc_constant,
+    envelope_packet_terms, equal_packet, packet_terms, token_accepted, token_min_amount,
+    token_min_per_packet, volatility_band_valid, PacketTerms,
+};
+pub use eligibility::{code_eligibility_status, eligibility_status, secret_commitment};
+pub use index::{
+    creator_envelope_at, creator_envelope_count, holder_claim_at, holder_claim_count, page,
+    page_range, pool_claim_count, pool_claim_record, PoolClaimRecord,
+};
+#[cfg(feature = "memory")]
+pub use memory::MemoryRuntime;
+pub use runtime::{Runtime, Storage};
@@ -0,0 +1,237 @@
+//! In-memory [`Runtime`] for tests, simulators and indexers replaying contract state.
+
+use alloc::collections::{BTreeMap, BTreeSet};
+use alloc::vec::Vec;
+
+use crate::constants::GAS_TOKEN;
+use crate::runtime::{Runtime, Storage};
+
+/// One `notify` call, in emission order.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Notification {
+    pub event: i64,
+    pub a: i64,
+    pub b: i64,
+    pub c: i64,
+}
+
+/// Chain state and syscall answers held in plain fields; adjust them between calls.
+#[derive(Clone, Debug)]
+pub struct MemoryRuntime {
+    pub storage: BTreeMap<i64, i64>,
+    pub now_ms: i64,
+    pub entropy: i64,
+    /// Answer to `block_index`.
+    pub block_index: i64,
+    /// Answers to `block_nonce`; unlisted blocks read as 0.
+    pub block_nonces: BTreeMap<i64, i64>,
+    /// Accounts whose witness is present on the current transaction.
+    pub witnesses: BTreeSet<i64>,
+    /// Accounts that resolve to deployed contracts.
+    pub contracts: BTreeSet<i64>,
+    pub direct_invocation: bool,
+    /// Answer to `calling_contract`; defaults to GAS.
+    pub calling_contract: i64,
+    pub notifications: Vec<Notification>,
+    /// `(nef, manifest)` pairs passed to `update_contract`.
+    pub updates: Vec<(i64, i64)>,
+    /// Arrays built by `array_new`/`array_append`; a handle is its index here.
+    pub arrays: Vec<Vec<i64>>,
+}
+
+impl Default for MemoryRuntime {
+    fn default() -> Self {
+        Self {
+            storage: BTreeMap::new(),
+            now_ms: 0,
+            entropy: 1,
+            block_index: 0,
+            block_nonces: BTreeMap::new(),
+            witnesses: BTreeSet::new(),
+            contracts: BTreeSet::new(),
+            direct_invocation: true,
+            calling_contract: GAS_TOKEN,
+            notifications: Vec::new(),
+            updates: Vec::new(),
+            arrays: Vec::new(),
+        }
+    }
+}
+
+impl MemoryRuntime {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Elements of the array behind `handle`.
+    pub fn array(&self, handle: i64) -> &[i64] {
+        &self.arrays[handle as usize]
+    }
+}
+
+impl Storage for MemoryRuntime {
+    fn get(&self, key: i64) -> i64 {
+        self.storage.get(&key).copied().unwrap_or(0)
+    }
+
+    fn put(&mut self, key: i64, value: i64) {
+        self.storage.insert(key, value);
+    }
+}
+
+impl Runtime for MemoryRuntime {
+    fn now_ms(&self) -> i64 {
+        self.now_ms
+    }
+
+    fn entropy(&self) -> i64 {
+        self.entropy
+    }
+
+    fn sha256_pair(&self, counter: i64, seed: i64) -> i64 {
+        let mut message = [0u8; 16];
+        message[..8].copy_from_slice(&seed.to_le_bytes());
+        message[8..].copy_from_slice(&counter.to_le_bytes());
+        let digest = sha256(&message);
+
+        let mut folded = [0u8; 8];
+        folded.copy_from_slice(&digest[..8]);
+        i64::from_le_bytes(folded)
+    }
+
+    fn sha256_raw(&self, raw: i64) -> i64 {
+        let digest = sha256(&raw.to_le_bytes());
+
+        let mut folded = [0u8; 8];
+        folded.copy_from_slice(&digest[..8]);
+        i64::from_le_bytes(folded)
+    }
+
+    fn check_witness(&self, account: i64) -> bool {
+        self.witnesses.contains(&account)
+    }
+
+    fn block_index(&self) -> i64 {
+        self.block_index
+    }
+
+    fn block_nonce(&self, index: i64) -> i64 {
+        self.block_nonces.get(&index).copied().unwrap_or(0)
+    }
+
+    fn is_contract_account(&self, account: i64) -> bool {
+        self.contracts.contains(&account)
+    }
+
+    fn calling_contract(&self) -> i64 {
+        self.calling_contract
+    }
+
+    fn is_direct_invocation(&self) -> bool {
+        self.direct_invocation
+    }
+
+    fn notify(&mut self, event: i64, a: i64, b: i64, c: i64) {
+        self.notifications.push(Notification { event, a, b, c });
+    }
+
+    fn update_contract(&mut self, nef: i64, manifest: i64) {
+        self.updates.push((nef, manifest));
+    }
+
+    fn array_new(&mut self) -> i64 {
+        self.arrays.push(Vec::new());
+        (self.arrays.len() - 1) as i64
+    }
+
+    fn array_append(&mut self, array: i64, value: i64) {
+        self.arrays[array as usize].push(value);
+    }
+
+    fn abort(&self) -> ! {
+        panic!("contract aborted");
+    }
+}
+
+const SHA256_K: [u32; 64] = [
+    0x428a2f98, 0x71374491, 0xb5c0fbcf, 0xe9b5dba5, 0x3956c25b, 0x59f111f1, 0x923f82a4, 0xab1c5ed5,
+    0xd807aa98, 0x12835b01, 0x243185be, 0x550c7dc3, 0x72be5d74, 0x80deb1fe, 0x9bdc06a7, 0xc19bf174,
+    0xe49b69c1, 0xefbe4786, 0x0fc19dc6, 0x240ca1cc, 0x2de92c6f, 0x4a7484aa, 0x5cb0a9dc, 0x76f988da,
+    0x983e5152, 0xa831c66d, 0xb00327c8, 0xbf597fc7, 0xc6e00bf3, 0xd5a79147, 0x06ca6351, 0x14292967,
+    0x27b70a85, 0x2e1b2138, 0x4d2c6dfc, 0x53380d13, 0x650a7354, 0x766a0abb, 0x81c2c92e, 0x92722c85,
+    0xa2bfe8a1, 0xa81a664b, 0xc24b8b70, 0xc76c51a3, 0xd192e819, 0xd6990624, 0xf40e3585, 0x106aa070,
+    0x19a4c116, 0x1e376c08, 0x2748774c, 0x34b0bcb5, 0x391c0cb3, 0x4ed8aa4a, 0x5b9cca4f, 0x682e6ff3,
+    0x748f82ee, 0x78a5636f, 0x84c87814, 0x8cc70208, 0x90befffa, 0xa4506ceb, 0xbef9a3f7, 0xc67178f2,
+];
+
+// Single-block SHA-256; every message the contract hashes fits in 55 bytes.
+pub(crate) fn sha256(message: &[u8]) -> [u8; 32] {
+    assert!(message.len() <= 55, "sha256 message exceeds one block");
+    let mut block = [0u8; 64];
+    block[..message.len()].copy_from_slice(message);
+    block[message.len()] = 0x80;
+    block[56..].copy_from_slice(&((message.len() as u64) * 8).to_be_bytes());
+
+    let mut w = [0u32; 64];
+    for (i, chunk) in block.chunks_exact(4).enumerate() {
+        w[i] = u32::from_be_bytes([chunk[0], chunk[1], chunk[2], chunk[3]]);
+    }
+    for i in 16..64 {
+        let s0 = w[i - 15].rotate_right(7) ^ w[i - 15].rotate_right(18) ^ (w[i - 15] >> 3);
+        let s1 = w[i - 2].rotate_right(17) ^ w[i - 2].rotate_right(19) ^ (w[i - 2] >> 10);
+        w[i] = w[i - 16]
+            .wrapping_add(s0)
+            .wrapping_add(w[i - 7])
+            .wrapping_add(s1);
+    }
+
+    let mut h: [u32; 8] = [
+        0x6a09e667, 0xbb67ae85, 0x3c6ef372, 0xa54ff53a, 0x510e527f, 0x9b05688c, 0x1f83d9ab, 0x5be0cd19,
+    ];
+    let [mut a, mut b, mut c, mut d, mut e, mut f, mut g, mut hh] = h;
+    for i in 0..64 {
+        let s1 = e.rotate_right(6) ^ e.rotate_right(11) ^ e.rotate_right(25);
+        let ch = (e & f) ^ (!e & g);
+        let t1 = hh
+            .wrapping_add(s1)
+            .wrapping_add(ch)
+            .wrapping_add(SHA256_K[i])
+            .wrapping_add(w[i]);
+        let s0 = a.rotate_right(2) ^ a.rotate_right(13) ^ a.rotate_right(22);
+        let maj = (a & b) ^ (a & c) ^ (b & c);
+        let t2 = s0.wrapping_add(maj);
+
+        hh = g;
+        g = f;
+        f = e;
+        e = d.wrapping_add(t1);
+        d = c;
+        c = b;
+        b = a;
+        a = t1.wrapping_add(t2);
+    }
+    for (state, v) in h.iter_mut().zip([a, b, c, d, e, f, g, hh]) {
+        *state = state.wrapping_add(v);
+    }
+
+    let mut digest = [0u8; 32];
+    for (chunk, v) in digest.chunks_exact_mut(4).zip(h) {
+        chunk.copy_from_slice(&v.to_be_bytes());
+    }
+    digest
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sha256_pair_matches_reference_digest() {
+        // sha256(16 zero bytes) = 374708fff7719dd5979ec875d56cd2286f6d3cf7ec317a3b25632aab28ec37bb
+        let rt = MemoryRuntime::new();
+        assert_eq!(
+            rt.sha256_pair(0, 0),
+            i64::from_le_bytes([0x37, 0x47, 0x08, 0xff, 0xf7, 0x71, 0x9d, 0xd5])
+        );
+    }
+}
@@ -0,0 +1,62 @@
+/// The contract's flat i64 key/value storage.
+pub trait Storage {
+    /// Returns the stored value, or 0 when the key is missing.
+    fn get(&self, key: i64) -> i64;
+
+    fn put(&mut self, key: i64, value: i64);
+
+    /// Returns the stored stack item as-is, for ByteString values (an upgrade's NEF and manifest)
+    /// that exceed NeoVM's integer size and must reach a syscall without integer conversion.
+    fn get_raw(&self, key: i64) -> i64 {
+        self.get(key)
+    }
+}
+
+/// Everything the contract needs from its host beyond storage.
+pub trait Runtime: Storage {
+    /// Block timestamp in milliseconds.
+    fn now_ms(&self) -> i64;
+
+    /// Positive per-invocation randomness used to seed packet rolls.
+    fn entropy(&self) -> i64;
+
+    /// SHA-256 over the little-endian (seed, counter) pair, folded to the first 8 digest bytes.
+    fn sha256_pair(&self, counter: i64, seed: i64) -> i64;
+
+    /// SHA-256 over a raw ByteString stack item (on the host, the value's 8 little-endian bytes),
+    /// folded like [`sha256_pair`](Self::sha256_pair).
+    fn sha256_raw(&self, raw: i64) -> i64;
+
+    fn check_witness(&self, account: i64) -> bool;
+
+    /// Index of the latest persisted block; the running transaction lands in the block after it.
+    fn block_index(&self) -> i64;
+
+    /// Nonce of the persisted block at `index`, as its i64 two's-complement view.
+    fn block_nonce(&self, index: i64) -> i64;
+
+    /// Whether `account` is a deployed contract rather than a user wallet.
+    fn is_contract_account(&self, account: i64) -> bool;
+
+    /// Script hash of the contract that called into this one (the NEP-17 token in `onNEP17Payment`),
+    /// in the same integer form as account arguments; GAS is [`GAS_TOKEN`](crate::constants::GAS_TOKEN).
+    fn calling_contract(&self) -> i64;
+
+    /// Whether the current call came straight from a transaction script (no contract in between).
+    fn is_direct_invocation(&self) -> bool;
+
+    /// Emits the `EV_*` notification `event` with three integer fields.
+    fn notify(&mut self, event: i64, a: i64, b: i64, c: i64);
+
+    fn update_contract(&mut self, nef: i64, manifest: i64);
+
+    /// Starts an empty `Array` result and returns its handle. Handles are opaque: only pass them
+    /// to [`array_append`](Self::array_append) or return them from a method.
+    fn array_new(&mut self) -> i64;
+
+    /// Appends `value` (an integer or another array handle) to the array `array`.
+    fn array_append(&mut self, array: i64, value: i64);
+
+    /// Faults the invocation, reverting every state change and the NEP-17 transfer that triggered it.
+    fn abort(&self) -> !;
+}
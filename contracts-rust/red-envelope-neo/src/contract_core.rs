@@ -1,3 +1,5 @@
 include!("rust_contract/constants.rs");
+include!("rust_contract/neovm.rs");
 include!("rust_contract/api.rs");
 include!("rust_contract/logic.rs");
+include!("rust_contract/methods.rs");
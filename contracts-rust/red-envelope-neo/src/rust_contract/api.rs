@@ -1,3 +1,8 @@
+// Each ABI entry point runs the generic contract against the live NeoVM host.
+fn contract() -> RedEnvelope<NeoVmRuntime> {
+    RedEnvelope::new(NeoVmRuntime)
+}
+
 #[neo_contract]
 pub struct RedEnvelopeRustContract;
 
@@ -15,490 +20,495 @@ impl RedEnvelopeRustContract {
 
     #[neo_method(name = "totalSupply")]
     pub fn total_supply() -> i64 {
-        get_key_i64(K_TOTAL_SUPPLY)
+        contract().total_supply()
     }
 
     #[neo_method(name = "balanceOf")]
     pub fn balance_of(account: i64) -> i64 {
-        if account == 0 {
-            return 0;
-        }
-        get_i64(k2(P_OWNER_BALANCE, account))
+        contract().balance_of(account)
     }
 
     #[neo_method(name = "ownerOf")]
     pub fn owner_of(token_id: i64) -> i64 {
-        get_i64(k2(P_TOKEN_OWNER, token_id))
+        contract().owner_of(token_id)
     }
 
     #[neo_method(name = "properties")]
     pub fn properties(token_id: i64) -> i64 {
-        if !token_exists(token_id) {
-            return 0;
-        }
-        env(P_TYPE, token_id)
+        contract().properties(token_id)
     }
 
     #[neo_method(name = "tokens")]
     pub fn tokens() -> i64 {
-        Self::total_supply()
+        contract().tokens()
     }
 
     #[neo_method(name = "tokensOf")]
     pub fn tokens_of(owner: i64) -> i64 {
-        Self::balance_of(owner)
+        contract().tokens_of(owner)
     }
 
     #[neo_method(name = "getOwner")]
     pub fn get_owner() -> i64 {
-        get_key_i64(K_OWNER)
+        contract().get_owner()
     }
 
     #[neo_method(name = "setOwner")]
     pub fn set_owner(new_owner: i64) {
-        if new_owner == 0 {
-            return;
-        }
-        put_key_i64(K_OWNER, new_owner);
+        contract().set_owner(new_owner);
     }
 
     #[neo_method(name = "isOwner")]
     pub fn is_owner() -> bool {
-        get_key_i64(K_OWNER) != 0
+        contract().is_owner()
     }
 
     #[neo_method(name = "verify")]
     pub fn verify() -> bool {
-        Self::is_owner()
+        contract().verify()
     }
 
     #[neo_method(name = "pause")]
     pub fn pause() {
-        put_key_i64(K_PAUSED, 1);
+        contract().pause();
     }
 
     #[neo_method(name = "resume")]
     pub fn resume() {
-        put_key_i64(K_PAUSED, 0);
+        contract().resume();
+    }
+
+    #[neo_method(name = "setPauseLevel")]
+    pub fn set_pause_level(level: i64) -> bool {
+        contract().set_pause_level(level)
+    }
+
+    #[neo_method(name = "getPauseLevel")]
+    pub fn get_pause_level() -> i64 {
+        contract().get_pause_level()
     }
 
     #[neo_method(name = "isPaused")]
     pub fn is_paused() -> bool {
-        get_key_i64(K_PAUSED) != 0
+        contract().is_paused()
     }
 
+    // Upgrades only run through the admin timelock (`scheduleUpgrade` + `executeAdminOperation`).
     #[neo_method(name = "update")]
     pub fn update(_nef: i64, _manifest: i64) {}
 
+    #[neo_method(name = "scheduleUpgrade")]
+    pub fn schedule_upgrade(nef: i64, manifest: i64) -> i64 {
+        contract().schedule_upgrade(nef, manifest)
+    }
+
+    #[neo_method(name = "cancelAdminOperation")]
+    pub fn cancel_admin_operation(op_id: i64) -> bool {
+        contract().cancel_admin_operation(op_id)
+    }
+
+    #[neo_method(name = "executeAdminOperation")]
+    pub fn execute_admin_operation(op_id: i64) -> bool {
+        contract().execute_admin_operation(op_id)
+    }
+
+    #[cfg(feature = "testnet")]
+    #[neo_method(name = "setTimeOverride")]
+    pub fn set_time_override(timestamp_ms: i64) -> bool {
+        contract().set_time_override(timestamp_ms)
+    }
+
+    #[cfg(feature = "testnet")]
+    #[neo_method(name = "clearTimeOverride")]
+    pub fn clear_time_override() -> bool {
+        contract().clear_time_override()
+    }
+
+    #[neo_method(name = "getAdminOperation")]
+    pub fn get_admin_operation(op_id: i64) -> i64 {
+        contract().get_admin_operation(op_id)
+    }
+
+    #[neo_method(name = "getAdminOperationEta")]
+    pub fn get_admin_operation_eta(op_id: i64) -> i64 {
+        contract().get_admin_operation_eta(op_id)
+    }
+
+    #[neo_method(name = "getAdminOperationKind")]
+    pub fn get_admin_operation_kind(op_id: i64) -> i64 {
+        contract().get_admin_operation_kind(op_id)
+    }
+
+    #[neo_method(name = "getAdminOperationConstant")]
+    pub fn get_admin_operation_constant(op_id: i64, slot: i64) -> i64 {
+        contract().get_admin_operation_constant(op_id, slot)
+    }
+
+    #[neo_method(name = "getAdminOperationNefHash")]
+    pub fn get_admin_operation_nef_hash(op_id: i64) -> i64 {
+        contract().get_admin_operation_nef_hash(op_id)
+    }
+
+    #[neo_method(name = "getAdminOperationManifestHash")]
+    pub fn get_admin_operation_manifest_hash(op_id: i64) -> i64 {
+        contract().get_admin_operation_manifest_hash(op_id)
+    }
+
     #[neo_method(name = "destroy")]
     pub fn destroy() {}
 
     #[neo_method(name = "_deploy")]
     pub fn contract_deploy(data: i64, update: bool) {
-        if !update {
-            put_key_i64(K_OWNER, data);
-            put_key_i64(K_NEXT_ID, 0);
-            put_key_i64(K_TOTAL_ENVELOPES, 0);
-            put_key_i64(K_TOTAL_DISTRIBUTED, 0);
-            put_key_i64(K_TIME_OVERRIDE, 0);
-            put_key_i64(K_TOTAL_SUPPLY, 0);
-            put_key_i64(K_PAUSED, 0);
-        } else if get_key_i64(K_OWNER) == 0 {
-            put_key_i64(K_OWNER, data);
-        }
+        contract().contract_deploy(data, update);
     }
 
     #[neo_method(name = "onNEP17Payment")]
     pub fn on_nep17_payment(from: i64, amount: i64, data: i64) {
-        if is_paused_flag() || from == 0 || amount < MIN_AMOUNT {
-            return;
-        }
-
-        let mut packet_count = 1;
-        let mut envelope_type = ENVELOPE_TYPE_SPREADING;
-        let mut expiry_ms = DEFAULT_EXPIRY_MS;
-
-        // Adapter v3 object[] encoding:
-        // spread => BASE + packetCount + expiryMs * MULTIPLIER
-        // pool   => BASE + TYPE_MULTIPLIER + packetCount + expiryMs * MULTIPLIER
-        if data >= ON_NEP17_ADAPTER_BASE {
-            let mut packed = data - ON_NEP17_ADAPTER_BASE;
-            if packed >= ON_NEP17_ADAPTER_TYPE_MULTIPLIER {
-                packed -= ON_NEP17_ADAPTER_TYPE_MULTIPLIER;
-                envelope_type = ENVELOPE_TYPE_POOL;
-            }
-            packet_count = packed % ON_NEP17_ADAPTER_EXPIRY_MULTIPLIER;
-            let adapter_expiry = packed / ON_NEP17_ADAPTER_EXPIRY_MULTIPLIER;
-            if adapter_expiry > 0 {
-                expiry_ms = adapter_expiry;
-            }
-        // Backward-compat for older sign-based adapter payloads.
-        } else if data <= -ON_NEP17_ADAPTER_BASE {
-            let packed = data.saturating_abs() - ON_NEP17_ADAPTER_BASE;
-            packet_count = packed % ON_NEP17_ADAPTER_EXPIRY_MULTIPLIER;
-            let adapter_expiry = packed / ON_NEP17_ADAPTER_EXPIRY_MULTIPLIER;
-            if adapter_expiry > 0 {
-                expiry_ms = adapter_expiry;
-            }
-            envelope_type = ENVELOPE_TYPE_POOL;
-        } else if data > 0 {
-            // Legacy packed-integer path for backward compatibility.
-            let packed_packets = data / ON_NEP17_LEGACY_PACK_BASE;
-            if packed_packets > 0 {
-                packet_count = packed_packets;
-            }
-            let packed_type = data - packed_packets * ON_NEP17_LEGACY_PACK_BASE;
-            if packed_type == ENVELOPE_TYPE_POOL {
-                envelope_type = ENVELOPE_TYPE_POOL;
-            }
-        }
-
-        if packet_count <= 0
-            || packet_count > MAX_PACKETS
-            || amount < packet_count * MIN_PER_PACKET
-            || !(envelope_type == ENVELOPE_TYPE_SPREADING || envelope_type == ENVELOPE_TYPE_POOL)
-        {
-            return;
-        }
-
-        let _ = create_envelope(
-            from,
-            amount,
-            packet_count,
-            expiry_ms,
-            envelope_type,
-            true,
-        );
+        contract().on_nep17_payment(from, amount, data);
     }
 
     #[neo_method(name = "tokenURI")]
     pub fn token_uri(token_id: i64) -> i64 {
-        if token_exists(token_id) {
-            token_id
-        } else {
-            0
-        }
+        contract().token_uri(token_id)
     }
 
     #[neo_method(name = "calculatePacketAmount")]
     pub fn calculate_packet_amount(envelope_id: i64, packet_index: i64) -> i64 {
-        if !exists(envelope_id) {
-            return 0;
-        }
-
-        let packet = env(P_PACKET, envelope_id);
-        let remaining = env(P_REMAINING, envelope_id);
-        let remaining_packets = packet.saturating_sub(packet_index);
-
-        if remaining <= 0 || remaining_packets <= 0 {
-            return 0;
-        }
-
-        calc_packet(
-            remaining,
-            remaining_packets,
-            0,
-            env(P_TOTAL, envelope_id),
-            packet,
-        )
+        contract().calculate_packet_amount(envelope_id, packet_index)
     }
 
     #[neo_method(name = "claimFromPool")]
     pub fn claim_from_pool(pool_id: i64, claimer: i64) -> i64 {
-        if is_paused_flag()
-            || claimer == 0
-            || !exists(pool_id)
-            || env(P_TYPE, pool_id) != ENVELOPE_TYPE_POOL
-            || env(P_ACTIVE, pool_id) == 0
-            || env(P_OPENED, pool_id) >= env(P_PACKET, pool_id)
-            || now_ms() > env(P_EXPIRY, pool_id)
-            || get_i64(k3(P_POOL_CLAIMED, pool_id, claimer)) > 0
-        {
-            return 0;
-        }
-
-        let opened = env(P_OPENED, pool_id);
-        let packet = env(P_PACKET, pool_id);
-        let remaining = env(P_REMAINING, pool_id);
-        let amount = calc_packet(
-            remaining,
-            packet.saturating_sub(opened),
-            0,
-            env(P_TOTAL, pool_id),
-            packet,
-        );
-        if amount <= 0 || amount > remaining {
-            return 0;
-        }
-
-        put_i64(k3(P_POOL_CLAIMED, pool_id, claimer), amount);
-
-        let opened2 = opened.saturating_add(1);
-        set_env(P_OPENED, pool_id, opened2);
-
-        let rem2 = remaining.saturating_sub(amount);
-        set_env(P_REMAINING, pool_id, rem2);
-        if rem2 == 0 || opened2 >= packet {
-            set_env(P_ACTIVE, pool_id, 0);
-        }
-
-        let claim_id = alloc_id();
-        set_env(P_CREATOR, claim_id, env(P_CREATOR, pool_id));
-        set_env(P_TOTAL, claim_id, amount);
-        set_env(P_PACKET, claim_id, 1);
-        set_env(P_TYPE, claim_id, ENVELOPE_TYPE_CLAIM);
-        set_env(P_PARENT, claim_id, pool_id);
-        set_env(P_OPENED, claim_id, 0);
-        set_env(P_REMAINING, claim_id, amount);
-        set_env(P_ACTIVE, claim_id, 1);
-        set_env(P_EXPIRY, claim_id, env(P_EXPIRY, pool_id));
-
-        mint_token(claim_id, claimer);
-        put_i64(k3(P_POOL_CLAIM_INDEX, pool_id, opened2), claim_id);
-
-        claim_id
+        contract().claim_from_pool(pool_id, claimer)
+    }
+
+    #[neo_method(name = "claimFromPoolWithCode")]
+    pub fn claim_from_pool_with_code(pool_id: i64, claimer: i64, code: i64) -> i64 {
+        contract().claim_from_pool_with_code(pool_id, claimer, code)
+    }
+
+    #[neo_method(name = "setPoolSecret")]
+    pub fn set_pool_secret(pool_id: i64, creator: i64, commitment: i64) -> bool {
+        contract().set_pool_secret(pool_id, creator, commitment)
+    }
+
+    #[neo_method(name = "setPoolAllowlist")]
+    pub fn set_pool_allowlist(pool_id: i64, creator: i64, root: i64) -> bool {
+        contract().set_pool_allowlist(pool_id, creator, root)
+    }
+
+    #[neo_method(name = "proveAllowlist")]
+    pub fn prove_allowlist(pool_id: i64, claimer: i64, sibling: i64) -> i64 {
+        contract().prove_allowlist(pool_id, claimer, sibling)
+    }
+
+    #[neo_method(name = "commitPoolClaim")]
+    pub fn commit_pool_claim(pool_id: i64, claimer: i64) -> bool {
+        contract().commit_pool_claim(pool_id, claimer)
+    }
+
+    #[neo_method(name = "revealPoolClaim")]
+    pub fn reveal_pool_claim(pool_id: i64, claimer: i64) -> i64 {
+        contract().reveal_pool_claim(pool_id, claimer)
+    }
+
+    #[neo_method(name = "expirePoolCommit")]
+    pub fn expire_pool_commit(pool_id: i64, claimer: i64) -> bool {
+        contract().expire_pool_commit(pool_id, claimer)
+    }
+
+    #[neo_method(name = "getPoolCommitState")]
+    pub fn get_pool_commit_state(pool_id: i64, claimer: i64) -> i64 {
+        contract().get_pool_commit_state(pool_id, claimer)
     }
 
     #[neo_method(name = "openClaim")]
     pub fn open_claim(claim_id: i64, opener: i64) -> i64 {
-        if is_paused_flag()
-            || opener == 0
-            || !exists(claim_id)
-            || env(P_TYPE, claim_id) != ENVELOPE_TYPE_CLAIM
-            || get_i64(k2(P_TOKEN_OWNER, claim_id)) != opener
-            || env(P_ACTIVE, claim_id) == 0
-            || env(P_OPENED, claim_id) > 0
-            || env(P_REMAINING, claim_id) <= 0
-            || now_ms() > env(P_EXPIRY, claim_id)
-        {
-            return 0;
-        }
-
-        let amount = env(P_REMAINING, claim_id);
-        set_env(P_OPENED, claim_id, 1);
-        set_env(P_REMAINING, claim_id, 0);
-        set_env(P_ACTIVE, claim_id, 0);
-        amount
+        contract().open_claim(claim_id, opener)
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    #[neo_method(name = "openClaims")]
+    pub fn open_claims(
+        opener: i64,
+        strict: bool,
+        id0: i64,
+        id1: i64,
+        id2: i64,
+        id3: i64,
+        id4: i64,
+        id5: i64,
+        id6: i64,
+        id7: i64,
+    ) -> i64 {
+        let ids: [i64; BATCH_MAX] = [id0, id1, id2, id3, id4, id5, id6, id7];
+        contract().open_claims(opener, strict, &ids)
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    #[neo_method(name = "claimFromPools")]
+    pub fn claim_from_pools(
+        claimer: i64,
+        strict: bool,
+        id0: i64,
+        id1: i64,
+        id2: i64,
+        id3: i64,
+        id4: i64,
+        id5: i64,
+        id6: i64,
+        id7: i64,
+    ) -> i64 {
+        let ids: [i64; BATCH_MAX] = [id0, id1, id2, id3, id4, id5, id6, id7];
+        contract().claim_from_pools(claimer, strict, &ids)
     }
 
     #[neo_method(name = "transferClaim")]
     pub fn transfer_claim(claim_id: i64, from: i64, to: i64) {
-        if is_paused_flag()
-            || from == 0
-            || to == 0
-            || !exists(claim_id)
-            || env(P_TYPE, claim_id) != ENVELOPE_TYPE_CLAIM
-            || get_i64(k2(P_TOKEN_OWNER, claim_id)) != from
-        {
-            return;
-        }
-
-        transfer_token(claim_id, from, to);
+        contract().transfer_claim(claim_id, from, to);
     }
 
     #[neo_method(name = "reclaimPool")]
     pub fn reclaim_pool(pool_id: i64, creator: i64) -> i64 {
-        if is_paused_flag()
-            || creator == 0
-            || !exists(pool_id)
-            || env(P_TYPE, pool_id) != ENVELOPE_TYPE_POOL
-            || env(P_CREATOR, pool_id) != creator
-            || now_ms() <= env(P_EXPIRY, pool_id)
-        {
-            return 0;
-        }
-
-        let mut refund = env(P_REMAINING, pool_id);
-        let opened = env(P_OPENED, pool_id);
-        let mut i = 1;
-        while i <= opened {
-            let claim_id = get_i64(k3(P_POOL_CLAIM_INDEX, pool_id, i));
-            if claim_id > 0
-                && env(P_TYPE, claim_id) == ENVELOPE_TYPE_CLAIM
-                && env(P_ACTIVE, claim_id) != 0
-            {
-                let rem = env(P_REMAINING, claim_id);
-                if rem > 0 {
-                    refund = refund.saturating_add(rem);
-                    set_env(P_REMAINING, claim_id, 0);
-                    set_env(P_ACTIVE, claim_id, 0);
-                }
-            }
-            i += 1;
-        }
-
-        if refund <= 0 {
-            return 0;
-        }
-
-        set_env(P_REMAINING, pool_id, 0);
-        set_env(P_ACTIVE, pool_id, 0);
-        refund
+        contract().reclaim_pool(pool_id, creator)
+    }
+
+    #[neo_method(name = "freeze")]
+    pub fn freeze(id: i64) -> bool {
+        contract().freeze(id)
+    }
+
+    #[neo_method(name = "unfreeze")]
+    pub fn unfreeze(id: i64) -> bool {
+        contract().unfreeze(id)
+    }
+
+    #[neo_method(name = "isFrozen")]
+    pub fn is_frozen_id(id: i64) -> bool {
+        contract().is_frozen_id(id)
     }
 
     #[neo_method(name = "getEnvelopeState")]
     pub fn get_envelope_state(envelope_id: i64) -> i64 {
-        if !exists(envelope_id) {
-            return 0;
-        }
-        env(P_REMAINING, envelope_id)
+        contract().get_envelope_state(envelope_id)
     }
 
     #[neo_method(name = "getClaimState")]
     pub fn get_claim_state(claim_id: i64) -> i64 {
-        if !exists(claim_id) || env(P_TYPE, claim_id) != ENVELOPE_TYPE_CLAIM {
-            return 0;
-        }
-        env(P_REMAINING, claim_id)
+        contract().get_claim_state(claim_id)
     }
 
     #[neo_method(name = "checkEligibility")]
     pub fn check_eligibility(envelope_id: i64, user: i64) -> i64 {
-        eligibility_status(envelope_id, user, false)
+        contract().check_eligibility(envelope_id, user)
     }
 
     #[neo_method(name = "checkOpenEligibility")]
     pub fn check_open_eligibility(envelope_id: i64, user: i64) -> i64 {
-        eligibility_status(envelope_id, user, true)
+        contract().check_open_eligibility(envelope_id, user)
+    }
+
+    #[neo_method(name = "checkCodeEligibility")]
+    pub fn check_code_eligibility(pool_id: i64, user: i64, code: i64) -> i64 {
+        contract().check_code_eligibility(pool_id, user, code)
     }
 
     #[neo_method(name = "hasOpened")]
     pub fn has_opened(envelope_id: i64, opener: i64) -> bool {
-        get_i64(k3(P_OPENED_AMOUNT, envelope_id, opener)) > 0
+        contract().has_opened(envelope_id, opener)
     }
 
     #[neo_method(name = "getOpenedAmount")]
     pub fn get_opened_amount(envelope_id: i64, opener: i64) -> i64 {
-        get_i64(k3(P_OPENED_AMOUNT, envelope_id, opener))
+        contract().get_opened_amount(envelope_id, opener)
     }
 
     #[neo_method(name = "hasClaimedFromPool")]
     pub fn has_claimed_from_pool(pool_id: i64, claimer: i64) -> bool {
-        get_i64(k3(P_POOL_CLAIMED, pool_id, claimer)) > 0
+        contract().has_claimed_from_pool(pool_id, claimer)
     }
 
     #[neo_method(name = "getPoolClaimedAmount")]
     pub fn get_pool_claimed_amount(pool_id: i64, claimer: i64) -> i64 {
-        get_i64(k3(P_POOL_CLAIMED, pool_id, claimer))
+        contract().get_pool_claimed_amount(pool_id, claimer)
     }
 
     #[neo_method(name = "getCalculationConstants")]
     pub fn get_calculation_constants() -> i64 {
-        MIN_AMOUNT
+        contract().get_calculation_constants()
+    }
+
+    #[neo_method(name = "getCalculationConstant")]
+    pub fn get_calculation_constant(slot: i64) -> i64 {
+        contract().get_calculation_constant(slot)
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    #[neo_method(name = "setCalculationConstants")]
+    pub fn set_calculation_constants(
+        min_amount: i64,
+        max_packets: i64,
+        min_per_packet: i64,
+        dense_low_bps: i64,
+        dense_high_bps: i64,
+        medium_low_bps: i64,
+        medium_high_bps: i64,
+        sparse_low_bps: i64,
+        sparse_high_bps: i64,
+        max_expiry_ms: i64,
+    ) -> i64 {
+        contract().set_calculation_constants(
+            min_amount,
+            max_packets,
+            min_per_packet,
+            dense_low_bps,
+            dense_high_bps,
+            medium_low_bps,
+            medium_high_bps,
+            sparse_low_bps,
+            sparse_high_bps,
+            max_expiry_ms,
+        )
+    }
+
+    #[neo_method(name = "setTokenConfig")]
+    pub fn set_token_config(token: i64, min_amount: i64, min_per_packet: i64) -> bool {
+        contract().set_token_config(token, min_amount, min_per_packet)
+    }
+
+    #[neo_method(name = "getTokenMinAmount")]
+    pub fn get_token_min_amount(token: i64) -> i64 {
+        contract().get_token_min_amount(token)
+    }
+
+    #[neo_method(name = "getTokenMinPerPacket")]
+    pub fn get_token_min_per_packet(token: i64) -> i64 {
+        contract().get_token_min_per_packet(token)
+    }
+
+    #[neo_method(name = "getEnvelopeToken")]
+    pub fn get_envelope_token(envelope_id: i64) -> i64 {
+        contract().get_envelope_token(envelope_id)
     }
 
     #[neo_method(name = "getTotalEnvelopes")]
     pub fn get_total_envelopes() -> i64 {
-        get_key_i64(K_TOTAL_ENVELOPES)
+        contract().get_total_envelopes()
     }
 
     #[neo_method(name = "getTotalDistributed")]
     pub fn get_total_distributed() -> i64 {
-        get_key_i64(K_TOTAL_DISTRIBUTED)
+        contract().get_total_distributed()
     }
 
     #[neo_method(name = "getPoolClaimIdByIndex")]
     pub fn get_pool_claim_id_by_index(pool_id: i64, claim_index: i64) -> i64 {
-        get_i64(k3(P_POOL_CLAIM_INDEX, pool_id, claim_index))
+        contract().get_pool_claim_id_by_index(pool_id, claim_index)
     }
 
-    #[neo_method(name = "transfer")]
-    pub fn transfer(to: i64, token_id: i64, _data: i64) -> bool {
-        if is_paused_flag() || to == 0 || !token_exists(token_id) {
-            return false;
-        }
+    #[neo_method(name = "getPoolClaimCount")]
+    pub fn get_pool_claim_count(pool_id: i64) -> i64 {
+        contract().get_pool_claim_count(pool_id)
+    }
+
+    #[neo_method(name = "getPoolClaimer")]
+    pub fn get_pool_claimer(pool_id: i64, claim_index: i64) -> i64 {
+        contract().get_pool_claimer(pool_id, claim_index)
+    }
+
+    #[neo_method(name = "getPoolClaims")]
+    pub fn get_pool_claims(pool_id: i64, offset: i64, limit: i64) -> i64 {
+        contract().get_pool_claims(pool_id, offset, limit)
+    }
 
-        let t = env(P_TYPE, token_id);
-        if !(t == ENVELOPE_TYPE_SPREADING || t == ENVELOPE_TYPE_CLAIM) {
-            return false;
-        }
+    #[neo_method(name = "isClaimOpened")]
+    pub fn is_claim_opened(claim_id: i64) -> bool {
+        contract().is_claim_opened(claim_id)
+    }
 
-        let from = get_i64(k2(P_TOKEN_OWNER, token_id));
-        if from == 0 {
-            return false;
-        }
+    #[neo_method(name = "getLuckiestClaimer")]
+    pub fn get_luckiest_claimer(envelope_id: i64) -> i64 {
+        contract().get_luckiest_claimer(envelope_id)
+    }
 
-        transfer_token(token_id, from, to)
+    #[neo_method(name = "getLuckiestAmount")]
+    pub fn get_luckiest_amount(envelope_id: i64) -> i64 {
+        contract().get_luckiest_amount(envelope_id)
+    }
+
+    #[neo_method(name = "getCreatorEnvelopeCount")]
+    pub fn get_creator_envelope_count(creator: i64) -> i64 {
+        contract().get_creator_envelope_count(creator)
+    }
+
+    #[neo_method(name = "getEnvelopeByCreatorIndex")]
+    pub fn get_envelope_by_creator_index(creator: i64, index: i64) -> i64 {
+        contract().get_envelope_by_creator_index(creator, index)
+    }
+
+    #[neo_method(name = "getHolderClaimCount")]
+    pub fn get_holder_claim_count(holder: i64) -> i64 {
+        contract().get_holder_claim_count(holder)
+    }
+
+    #[neo_method(name = "getClaimByHolderIndex")]
+    pub fn get_claim_by_holder_index(holder: i64, index: i64) -> i64 {
+        contract().get_claim_by_holder_index(holder, index)
+    }
+
+    #[neo_method(name = "getEnvelopesByCreator")]
+    pub fn get_envelopes_by_creator(creator: i64, offset: i64, limit: i64) -> i64 {
+        contract().get_envelopes_by_creator(creator, offset, limit)
+    }
+
+    #[neo_method(name = "getClaimsByHolder")]
+    pub fn get_claims_by_holder(holder: i64, offset: i64, limit: i64) -> i64 {
+        contract().get_claims_by_holder(holder, offset, limit)
+    }
+
+    #[neo_method(name = "transfer")]
+    pub fn transfer(to: i64, token_id: i64, data: i64) -> bool {
+        contract().transfer(to, token_id, data)
     }
 
     #[neo_method(name = "openEnvelope")]
     pub fn open_envelope(envelope_id: i64, opener: i64) -> i64 {
-        if is_paused_flag()
-            || opener == 0
-            || !exists(envelope_id)
-            || env(P_TYPE, envelope_id) != ENVELOPE_TYPE_SPREADING
-            || env(P_ACTIVE, envelope_id) == 0
-            || env(P_OPENED, envelope_id) >= env(P_PACKET, envelope_id)
-            || now_ms() > env(P_EXPIRY, envelope_id)
-            || get_i64(k2(P_TOKEN_OWNER, envelope_id)) != opener
-            || get_i64(k3(P_OPENED_AMOUNT, envelope_id, opener)) > 0
-        {
-            return 0;
-        }
-
-        let remaining = env(P_REMAINING, envelope_id);
-        let opened = env(P_OPENED, envelope_id);
-        let packet = env(P_PACKET, envelope_id);
-        let total_amount = env(P_TOTAL, envelope_id);
-        let amount = calc_packet(
-            remaining,
-            packet.saturating_sub(opened),
-            0,
-            total_amount,
-            packet,
-        );
-        if amount <= 0 || amount > remaining {
-            return 0;
-        }
-
-        put_i64(k3(P_OPENED_AMOUNT, envelope_id, opener), amount);
-        set_env(P_OPENED, envelope_id, opened.saturating_add(1));
-
-        let rem2 = remaining.saturating_sub(amount);
-        set_env(P_REMAINING, envelope_id, rem2);
-        if rem2 == 0 || opened.saturating_add(1) >= packet {
-            set_env(P_ACTIVE, envelope_id, 0);
-        }
-
-        amount
+        contract().open_envelope(envelope_id, opener)
     }
 
     #[neo_method(name = "transferEnvelope")]
-    pub fn transfer_envelope(envelope_id: i64, from: i64, to: i64, _data: i64) {
-        if is_paused_flag()
-            || from == 0
-            || to == 0
-            || !exists(envelope_id)
-            || env(P_TYPE, envelope_id) != ENVELOPE_TYPE_SPREADING
-            || get_i64(k2(P_TOKEN_OWNER, envelope_id)) != from
-        {
-            return;
-        }
+    pub fn transfer_envelope(envelope_id: i64, from: i64, to: i64, data: i64) {
+        contract().transfer_envelope(envelope_id, from, to, data);
+    }
 
-        let _ = transfer_token(envelope_id, from, to);
+    #[neo_method(name = "setEnvelopeRecipient")]
+    pub fn set_envelope_recipient(envelope_id: i64, creator: i64, recipient: i64) -> bool {
+        contract().set_envelope_recipient(envelope_id, creator, recipient)
+    }
+
+    #[neo_method(name = "getEnvelopeStart")]
+    pub fn get_envelope_start(envelope_id: i64) -> i64 {
+        contract().get_envelope_start(envelope_id)
+    }
+
+    #[neo_method(name = "getEnvelopeRecipient")]
+    pub fn get_envelope_recipient(envelope_id: i64) -> i64 {
+        contract().get_envelope_recipient(envelope_id)
+    }
+
+    #[neo_method(name = "cancelEnvelope")]
+    pub fn cancel_envelope(envelope_id: i64, creator: i64) -> i64 {
+        contract().cancel_envelope(envelope_id, creator)
     }
 
     #[neo_method(name = "reclaimEnvelope")]
     pub fn reclaim_envelope(envelope_id: i64, creator: i64) -> i64 {
-        if is_paused_flag()
-            || creator == 0
-            || !exists(envelope_id)
-            || env(P_TYPE, envelope_id) != ENVELOPE_TYPE_SPREADING
-            || env(P_CREATOR, envelope_id) != creator
-            || env(P_ACTIVE, envelope_id) == 0
-            || now_ms() <= env(P_EXPIRY, envelope_id)
-        {
-            return 0;
-        }
-
-        let refund = env(P_REMAINING, envelope_id);
-        if refund <= 0 {
-            return 0;
-        }
-
-        set_env(P_REMAINING, envelope_id, 0);
-        set_env(P_ACTIVE, envelope_id, 0);
-        refund
+        contract().reclaim_envelope(envelope_id, creator)
+    }
+
+    #[neo_method(name = "reclaimAllExpired")]
+    pub fn reclaim_all_expired(creator: i64, max_count: i64) -> i64 {
+        contract().reclaim_all_expired(creator, max_count)
     }
 
     #[neo_method(name = "_initialize")]
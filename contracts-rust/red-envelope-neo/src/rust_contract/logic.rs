@@ -1,409 +1,620 @@
-fn create_envelope(
-    from: i64,
-    amount: i64,
-    packet_count: i64,
-    expiry_ms: i64,
-    envelope_type: i64,
-    update_totals: bool,
-) -> i64 {
-    if from == 0
-        || amount < MIN_AMOUNT
-        || packet_count <= 0
-        || packet_count > MAX_PACKETS
-        || amount < packet_count.saturating_mul(MIN_PER_PACKET)
-        || expiry_ms <= 0
-        || expiry_ms > MAX_EXPIRY_MS
-        || !(envelope_type == ENVELOPE_TYPE_SPREADING || envelope_type == ENVELOPE_TYPE_POOL)
-    {
-        return 0;
-    }
-
-    let id = alloc_id();
-    set_env(P_CREATOR, id, from);
-    set_env(P_TOTAL, id, amount);
-    set_env(P_PACKET, id, packet_count);
-    set_env(P_TYPE, id, envelope_type);
-    set_env(P_PARENT, id, 0);
-    set_env(P_OPENED, id, 0);
-    set_env(P_REMAINING, id, amount);
-    set_env(P_ACTIVE, id, 1);
-    set_env(P_EXPIRY, id, now_ms().saturating_add(expiry_ms));
-
-    if envelope_type == ENVELOPE_TYPE_SPREADING {
-        mint_token(id, from);
-    }
-
-    if update_totals {
-        put_key_i64(
-            K_TOTAL_ENVELOPES,
-            get_key_i64(K_TOTAL_ENVELOPES).saturating_add(1),
-        );
-        put_key_i64(
-            K_TOTAL_DISTRIBUTED,
-            get_key_i64(K_TOTAL_DISTRIBUTED).saturating_add(amount),
-        );
-    }
-
-    id
+/// The contract's state machine, generic over where storage and syscalls come from.
+///
+/// `api.rs` runs it over [`NeoVmRuntime`]; tests and tools use `red_envelope_core::MemoryRuntime`.
+pub struct RedEnvelope<R: red_envelope_core::Runtime> {
+    rt: R,
 }
 
-fn eligibility_status(envelope_id: i64, user: i64, include_action_checks: bool) -> i64 {
-    if !exists(envelope_id) {
-        return E_NOT_FOUND;
-    }
+impl<R: red_envelope_core::Runtime> RedEnvelope<R> {
+    pub fn new(rt: R) -> Self {
+        Self { rt }
+    }
+
+    pub fn runtime(&self) -> &R {
+        &self.rt
+    }
+
+    pub fn runtime_mut(&mut self) -> &mut R {
+        &mut self.rt
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn create_envelope(
+        &mut self,
+        from: i64,
+        amount: i64,
+        packet_count: i64,
+        expiry_ms: i64,
+        envelope_type: i64,
+        flags: i64,
+        pay_token: i64,
+        start_delay_ms: i64,
+        update_totals: bool,
+    ) -> i64 {
+        if from == 0
+            || !red_envelope_core::token_accepted(&self.rt, pay_token)
+            || amount < red_envelope_core::token_min_amount(&self.rt, pay_token)
+            || packet_count <= 0
+            || packet_count > self.calc_constant(CONST_MAX_PACKETS)
+            || amount
+                < packet_count
+                    .saturating_mul(red_envelope_core::token_min_per_packet(&self.rt, pay_token))
+            || expiry_ms <= 0
+            || expiry_ms > self.calc_constant(CONST_MAX_EXPIRY_MS)
+            || start_delay_ms < 0
+            || !(envelope_type == ENVELOPE_TYPE_SPREADING
+                || envelope_type == ENVELOPE_TYPE_POOL
+                || envelope_type == ENVELOPE_TYPE_EQUAL)
+            || flags & !ENVELOPE_FLAGS_KNOWN != 0
+            || (flags & ENVELOPE_FLAG_COMMIT_REVEAL != 0 && envelope_type != ENVELOPE_TYPE_POOL)
+            || (flags & (ENVELOPE_FLAG_SECRET | ENVELOPE_FLAG_ALLOWLIST) != 0
+                && envelope_type == ENVELOPE_TYPE_SPREADING)
+            || (flags & ENVELOPE_FLAG_SECRET != 0 && flags & ENVELOPE_FLAG_COMMIT_REVEAL != 0)
+            || (flags & ENVELOPE_FLAG_TARGETED != 0 && envelope_type != ENVELOPE_TYPE_SPREADING)
+            || (flags & ENVELOPE_FLAG_RECIPIENT_ONLY != 0 && flags & ENVELOPE_FLAG_TARGETED == 0)
+        {
+            return 0;
+        }
 
-    if env(P_ACTIVE, envelope_id) == 0 {
-        return E_NOT_ACTIVE;
-    }
+        let id = self.alloc_id();
+        self.set_env(P_CREATOR, id, from);
+        self.index_creator_envelope(from, id);
+        self.set_env(P_TOTAL, id, amount);
+        self.set_env(P_PACKET, id, packet_count);
+        self.set_env(P_TYPE, id, envelope_type);
+        self.set_env(P_PARENT, id, 0);
+        self.set_env(P_OPENED, id, 0);
+        self.set_env(P_REMAINING, id, amount);
+        self.set_env(P_ACTIVE, id, 1);
+        // The expiry window runs from the scheduled start, not from the deposit.
+        let start = self.now_ms().saturating_add(start_delay_ms);
+        if start_delay_ms > 0 {
+            self.set_env(P_START, id, start);
+        }
+        self.set_env(P_EXPIRY, id, start.saturating_add(expiry_ms));
+        if flags != 0 {
+            self.set_env(P_FLAGS, id, flags);
+        }
+        let terms = red_envelope_core::packet_terms(&self.rt, pay_token, packet_count);
+        self.set_env(P_MIN_PER_PACKET, id, terms.min_per_packet);
+        self.set_env(P_LOW_BPS, id, terms.low_bps);
+        self.set_env(P_HIGH_BPS, id, terms.high_bps);
+        if pay_token != 0 {
+            self.set_env(P_PAY_TOKEN, id, pay_token);
+            self.emit(EV_TOKEN_ENVELOPE_CREATED, id, pay_token, amount);
+        }
 
-    if include_action_checks {
-        if now_ms() > env(P_EXPIRY, envelope_id) {
-            return E_EXPIRED;
+        if envelope_type == ENVELOPE_TYPE_SPREADING && flags & ENVELOPE_FLAG_TARGETED == 0 {
+            self.mint_token(id, from);
         }
 
-        if env(P_OPENED, envelope_id) >= env(P_PACKET, envelope_id)
-            || env(P_REMAINING, envelope_id) <= 0
-        {
-            return E_DEPLETED;
+        if update_totals {
+            self.put_key_i64(
+                K_TOTAL_ENVELOPES,
+                self.get_key_i64(K_TOTAL_ENVELOPES).saturating_add(1),
+            );
+            // GAS-denominated only; token amounts are not comparable units.
+            if pay_token == 0 {
+                self.put_key_i64(
+                    K_TOTAL_DISTRIBUTED,
+                    self.get_key_i64(K_TOTAL_DISTRIBUTED).saturating_add(amount),
+                );
+            }
         }
 
-        let t = env(P_TYPE, envelope_id);
-        if t == ENVELOPE_TYPE_POOL {
-            if get_i64(k3(P_POOL_CLAIMED, envelope_id, user)) > 0 {
-                return E_ALREADY_CLAIMED;
+        id
+    }
+
+    // Every envelope in a batch is identical, so they all pass or fail validation together: a
+    // refused first envelope creates nothing (returns 0), a created one means the rest follow.
+    fn create_envelope_batch(
+        &mut self,
+        from: i64,
+        amount: i64,
+        pay_token: i64,
+        batch: red_envelope_core::BatchTerms,
+    ) -> i64 {
+        if batch.count < 2 || batch.count > MAX_BATCH_ENVELOPES || amount % batch.count != 0 {
+            return 0;
+        }
+
+        let per_envelope = amount / batch.count;
+        let mut created = 0;
+        let mut first_id = 0;
+        while created < batch.count {
+            let id = self.create_envelope(
+                from,
+                per_envelope,
+                batch.packet_count,
+                batch.expiry_ms,
+                batch.envelope_type,
+                0,
+                pay_token,
+                0,
+                true,
+            );
+            if id == 0 {
+                break;
             }
-        } else if t == ENVELOPE_TYPE_SPREADING || t == ENVELOPE_TYPE_CLAIM {
-            if get_i64(k2(P_TOKEN_OWNER, envelope_id)) != user {
-                return E_NOT_HOLDER;
+            if first_id == 0 {
+                first_id = id;
             }
+            created += 1;
+        }
 
-            if t == ENVELOPE_TYPE_SPREADING && get_i64(k3(P_OPENED_AMOUNT, envelope_id, user)) > 0 {
-                return E_ALREADY_OPENED;
-            }
+        if created > 0 {
+            self.emit(EV_ENVELOPE_BATCH_CREATED, first_id, created, per_envelope);
+        }
+        first_id
+    }
+
+    // Only the creator, in the envelope's own asset, before expiry. Depleted envelopes come back
+    // to life when the top-up adds packets; cancelled ones (nothing opened) stay closed.
+    fn top_up_envelope(
+        &mut self,
+        from: i64,
+        amount: i64,
+        pay_token: i64,
+        top_up: red_envelope_core::TopUpTerms,
+    ) -> bool {
+        let id = top_up.envelope_id;
+        let t = self.env(P_TYPE, id);
+        let opened = self.env(P_OPENED, id);
+        let packet = self.env(P_PACKET, id).saturating_add(top_up.add_packets);
+        let remaining = self.env(P_REMAINING, id).saturating_add(amount);
+        let expiry = self.env(P_EXPIRY, id).saturating_add(top_up.extend_ms);
+        let min_per_packet = self.packet_terms(id).min_per_packet;
+        if amount < min_per_packet
+            || !self.exists(id)
+            || self.is_frozen(id)
+            || t == ENVELOPE_TYPE_CLAIM
+            || self.env(P_CREATOR, id) != from
+            || self.env(P_PAY_TOKEN, id) != pay_token
+            || (self.env(P_ACTIVE, id) == 0 && opened == 0)
+            // Equal shares are fixed by the first claim.
+            || (t == ENVELOPE_TYPE_EQUAL && opened > 0)
+            || self.now_ms() > self.env(P_EXPIRY, id)
+            || packet > self.calc_constant(CONST_MAX_PACKETS)
+            || opened >= packet
+            || remaining < packet.saturating_sub(opened).saturating_mul(min_per_packet)
+            // Like creation, the window of a scheduled envelope runs from its start.
+            || expiry.saturating_sub(self.now_ms().max(self.env(P_START, id)))
+                > self.calc_constant(CONST_MAX_EXPIRY_MS)
+        {
+            return false;
+        }
 
-            if t == ENVELOPE_TYPE_CLAIM && env(P_OPENED, envelope_id) > 0 {
-                return E_ALREADY_OPENED;
-            }
-        } else {
-            return E_INVALID_TYPE;
+        self.set_env(P_TOTAL, id, self.env(P_TOTAL, id).saturating_add(amount));
+        self.set_env(P_REMAINING, id, remaining);
+        self.set_env(P_PACKET, id, packet);
+        self.set_env(P_EXPIRY, id, expiry);
+        self.set_env(P_ACTIVE, id, 1);
+        if pay_token == 0 {
+            self.put_key_i64(
+                K_TOTAL_DISTRIBUTED,
+                self.get_key_i64(K_TOTAL_DISTRIBUTED).saturating_add(amount),
+            );
         }
+
+        self.emit(EV_ENVELOPE_TOPPED_UP, id, amount, packet);
+        true
     }
 
-    ELIGIBILITY_OK
-}
+    fn eligibility_status(&self, envelope_id: i64, user: i64, include_action_checks: bool) -> i64 {
+        red_envelope_core::eligibility_status(
+            &self.rt,
+            envelope_id,
+            user,
+            include_action_checks,
+            self.now_ms(),
+        )
+    }
 
-fn mint_token(token_id: i64, owner: i64) {
-    put_i64(k2(P_TOKEN_OWNER, token_id), owner);
+    // Shared by the plain and code-gated claim entry points; secret checks happen before this.
+    fn claim_pool_slot(&mut self, pool_id: i64, claimer: i64) -> i64 {
+        if !self.pool_claimable(pool_id, claimer) {
+            return 0;
+        }
 
-    let bal_key = k2(P_OWNER_BALANCE, owner);
-    let bal = get_i64(bal_key);
-    put_i64(bal_key, bal.saturating_add(1));
+        self.mint_pool_claim(pool_id, claimer, self.rt.entropy())
+    }
+
+    fn pool_claimable(&self, pool_id: i64, claimer: i64) -> bool {
+        !(self.is_paused_flag()
+            || claimer == 0
+            || !self.is_direct_user_invocation()
+            || self.is_contract_account(claimer)
+            || !self.exists(pool_id)
+            || self.is_frozen(pool_id)
+            || !self.is_pool_type(pool_id)
+            || self.is_commit_reveal(pool_id)
+            || self.env(P_ACTIVE, pool_id) == 0
+            || self.env(P_OPENED, pool_id) >= self.env(P_PACKET, pool_id)
+            || !self.is_started(pool_id)
+            || self.now_ms() > self.env(P_EXPIRY, pool_id)
+            || self.get_i64(k3(P_POOL_CLAIMED, pool_id, claimer)) > 0
+            || !self.allowlist_proven(pool_id, claimer))
+    }
+
+    fn claim_openable(&self, claim_id: i64, opener: i64) -> bool {
+        !(self.is_paused_flag()
+            || opener == 0
+            || !self.is_direct_user_invocation()
+            || self.is_contract_account(opener)
+            || !self.exists(claim_id)
+            || self.is_frozen(claim_id)
+            || self.env(P_TYPE, claim_id) != ENVELOPE_TYPE_CLAIM
+            || self.get_i64(k2(P_TOKEN_OWNER, claim_id)) != opener
+            || self.env(P_ACTIVE, claim_id) == 0
+            || self.env(P_OPENED, claim_id) > 0
+            || self.env(P_REMAINING, claim_id) <= 0
+            || self.now_ms() > self.env(P_EXPIRY, claim_id))
+    }
+
+    // Strict batches run only when every non-zero id is distinct and would succeed on its own.
+    fn batch_ready(&self, ids: &[i64], item_ok: impl Fn(&Self, i64) -> bool) -> bool {
+        ids.iter().enumerate().all(|(i, &id)| {
+            id == 0 || (!ids[..i].contains(&id) && item_ok(self, id))
+        })
+    }
+
+    // Caller has already validated the pool, claimer and slot availability.
+    fn mint_pool_claim(&mut self, pool_id: i64, claimer: i64, seed: i64) -> i64 {
+        let opened = self.env(P_OPENED, pool_id);
+        let packet = self.env(P_PACKET, pool_id);
+        let remaining = self.env(P_REMAINING, pool_id);
+        let amount = self.packet_amount(pool_id, remaining, packet.saturating_sub(opened), seed);
+        if amount <= 0 || amount > remaining {
+            return 0;
+        }
 
-    put_key_i64(
-        K_TOTAL_SUPPLY,
-        get_key_i64(K_TOTAL_SUPPLY).saturating_add(1),
-    );
-}
+        self.put_i64(k3(P_POOL_CLAIMED, pool_id, claimer), amount);
 
-fn transfer_token(token_id: i64, from: i64, to: i64) -> bool {
-    if from == 0 || to == 0 {
-        return false;
-    }
-    if get_i64(k2(P_TOKEN_OWNER, token_id)) != from {
-        return false;
+        let opened2 = opened.saturating_add(1);
+        self.set_env(P_OPENED, pool_id, opened2);
+
+        let rem2 = remaining.saturating_sub(amount);
+        self.set_env(P_REMAINING, pool_id, rem2);
+        let depleted = rem2 == 0 || opened2 >= packet;
+        if depleted {
+            self.set_env(P_ACTIVE, pool_id, 0);
+        }
+        self.record_luckiest(pool_id, claimer, amount, depleted);
+
+        let claim_id = self.alloc_id();
+        self.set_env(P_CREATOR, claim_id, self.env(P_CREATOR, pool_id));
+        self.set_env(P_TOTAL, claim_id, amount);
+        self.set_env(P_PACKET, claim_id, 1);
+        self.set_env(P_TYPE, claim_id, ENVELOPE_TYPE_CLAIM);
+        self.set_env(P_PARENT, claim_id, pool_id);
+        self.set_env(P_CLAIMER, claim_id, claimer);
+        self.set_env(P_OPENED, claim_id, 0);
+        self.set_env(P_REMAINING, claim_id, amount);
+        self.set_env(P_ACTIVE, claim_id, 1);
+        self.set_env(P_EXPIRY, claim_id, self.env(P_EXPIRY, pool_id));
+        let pay_token = self.env(P_PAY_TOKEN, pool_id);
+        if pay_token != 0 {
+            self.set_env(P_PAY_TOKEN, claim_id, pay_token);
+        }
+
+        self.mint_token(claim_id, claimer);
+        self.index_holder_claim(claimer, claim_id);
+        self.put_i64(k3(P_POOL_CLAIM_INDEX, pool_id, opened2), claim_id);
+
+        claim_id
     }
 
-    put_i64(k2(P_TOKEN_OWNER, token_id), to);
+    // Emits `LuckiestUpdated` for a new best packet and `LuckiestFinalized` once the last packet
+    // is out.
+    fn record_luckiest(&mut self, envelope_id: i64, holder: i64, amount: i64, depleted: bool) {
+        if amount > self.env(P_LUCKIEST_AMOUNT, envelope_id) {
+            self.set_env(P_LUCKIEST_AMOUNT, envelope_id, amount);
+            self.set_env(P_LUCKIEST_HOLDER, envelope_id, holder);
+            self.emit(EV_LUCKIEST_UPDATED, envelope_id, holder, amount);
+        }
+        if depleted {
+            self.emit(
+                EV_LUCKIEST_FINALIZED,
+                envelope_id,
+                self.env(P_LUCKIEST_HOLDER, envelope_id),
+                self.env(P_LUCKIEST_AMOUNT, envelope_id),
+            );
+        }
+    }
 
-    let from_key = k2(P_OWNER_BALANCE, from);
-    let to_key = k2(P_OWNER_BALANCE, to);
+    // Equal-split envelopes skip the random roll entirely.
+    fn packet_amount(&self, envelope_id: i64, remaining: i64, packets_left: i64, seed: i64) -> i64 {
+        let total = self.env(P_TOTAL, envelope_id);
+        let packet = self.env(P_PACKET, envelope_id);
+        if self.env(P_TYPE, envelope_id) == ENVELOPE_TYPE_EQUAL {
+            return red_envelope_core::equal_packet(remaining, packets_left, total, packet);
+        }
+        red_envelope_core::calc_packet_with_terms(
+            &self.rt,
+            self.packet_terms(envelope_id),
+            remaining,
+            packets_left,
+            0,
+            total,
+            packet,
+            seed,
+        )
+    }
+
+    fn packet_terms(&self, envelope_id: i64) -> red_envelope_core::PacketTerms {
+        red_envelope_core::envelope_packet_terms(
+            &self.rt,
+            envelope_id,
+            self.env(P_PAY_TOKEN, envelope_id),
+            self.env(P_PACKET, envelope_id),
+        )
+    }
+
+    // Expired with nothing a reclaim could still pay: no remainder and no live claim NFT left.
+    fn is_reclaim_settled(&self, id: i64) -> bool {
+        if self.now_ms() <= self.env(P_EXPIRY, id) || self.is_frozen(id) {
+            return false;
+        }
+        if !self.is_pool_type(id) {
+            return self.env(P_REMAINING, id) <= 0 || self.env(P_ACTIVE, id) == 0;
+        }
+        if self.env(P_REMAINING, id) > 0 {
+            return false;
+        }
 
-    let from_bal = get_i64(from_key);
-    if from_bal > 0 {
-        put_i64(from_key, from_bal - 1);
+        let opened = self.env(P_OPENED, id);
+        let mut i = 1;
+        while i <= opened {
+            let claim_id = self.get_i64(k3(P_POOL_CLAIM_INDEX, id, i));
+            if claim_id > 0
+                && self.env(P_ACTIVE, claim_id) != 0
+                && self.env(P_REMAINING, claim_id) > 0
+            {
+                return false;
+            }
+            i += 1;
+        }
+        true
     }
 
-    let to_bal = get_i64(to_key);
-    put_i64(to_key, to_bal.saturating_add(1));
+    fn is_pool_type(&self, id: i64) -> bool {
+        let t = self.env(P_TYPE, id);
+        t == ENVELOPE_TYPE_POOL || t == ENVELOPE_TYPE_EQUAL
+    }
 
-    true
-}
+    fn is_commit_reveal(&self, pool_id: i64) -> bool {
+        self.env(P_FLAGS, pool_id) & ENVELOPE_FLAG_COMMIT_REVEAL != 0
+    }
 
-fn ctx() -> i64 {
-    unsafe { neo_storage_get_context() }
-}
+    fn is_secret_pool(&self, pool_id: i64) -> bool {
+        self.env(P_FLAGS, pool_id) & ENVELOPE_FLAG_SECRET != 0
+    }
 
-#[inline(always)]
-fn key(prefix: u8) -> i64 {
-    prefix as i64
-}
+    fn is_started(&self, id: i64) -> bool {
+        self.now_ms() >= self.env(P_START, id)
+            && !red_envelope_core::keys::awaiting_recipient(&self.rt, id)
+    }
 
-#[inline(always)]
-fn mix64(mut x: u64) -> u64 {
-    x ^= x >> 30;
-    x = x.wrapping_mul(0xbf58_476d_1ce4_e5b9);
-    x ^= x >> 27;
-    x = x.wrapping_mul(0x94d0_49bb_1331_11eb);
-    x ^ (x >> 31)
-}
+    fn is_recipient_locked(&self, id: i64) -> bool {
+        self.env(P_FLAGS, id) & ENVELOPE_FLAG_RECIPIENT_ONLY != 0
+    }
 
-#[inline(always)]
-fn positive_key(x: u64) -> i64 {
-    let v = (x & 0x7fff_ffff_ffff_ffff) as i64;
-    if v == 0 {
-        1
-    } else {
-        v
+    fn allowlist_proven(&self, pool_id: i64, account: i64) -> bool {
+        red_envelope_core::allowlist_proven(&self.rt, pool_id, account)
     }
-}
 
-#[inline(always)]
-fn k2(prefix: u8, a: i64) -> i64 {
-    let seed = ((prefix as u64) << 56) ^ (a as u64);
-    positive_key(mix64(seed))
-}
+    // Secrets and allowlist roots are write-once and frozen once the first claim lands.
+    fn can_set_pool_gate(&self, pool_id: i64, creator: i64, flag: i64, prefix: u8) -> bool {
+        !self.is_paused_flag()
+            && self.has_witness(creator)
+            && self.is_direct_user_invocation()
+            && self.env(P_FLAGS, pool_id) & flag != 0
+            && self.env(P_CREATOR, pool_id) == creator
+            && self.env(prefix, pool_id) == 0
+            && self.env(P_OPENED, pool_id) == 0
+    }
 
-#[inline(always)]
-fn k3(prefix: u8, a: i64, b: i64) -> i64 {
-    let seed = ((prefix as u64) << 56) ^ mix64(a as u64) ^ mix64((b as u64).rotate_left(17));
-    positive_key(mix64(seed))
-}
+    fn schedule_admin_op(&mut self, kind: i64, arg0: i64, arg1: i64) -> i64 {
+        if !self.owner_witness() {
+            return 0;
+        }
 
-fn put_key_i64(prefix: u8, v: i64) {
-    put_i64(key(prefix), v);
-}
+        let op_id = self.get_key_i64(K_NEXT_ADMIN_OP).saturating_add(1);
+        self.put_key_i64(K_NEXT_ADMIN_OP, op_id);
 
-fn get_key_i64(prefix: u8) -> i64 {
-    get_i64(key(prefix))
-}
+        let eta = self.now_ms().saturating_add(ADMIN_TIMELOCK_DELAY_MS);
+        self.set_env(A_KIND, op_id, kind);
+        self.set_env(A_ARG0, op_id, arg0);
+        self.set_env(A_ARG1, op_id, arg1);
+        self.set_env(A_ETA, op_id, eta);
+        self.set_env(A_STATE, op_id, ADMIN_OP_QUEUED);
 
-fn put_i64(key: i64, v: i64) {
-    let ctx = ctx();
-    unsafe {
-        neo_storage_put(key, v, ctx);
+        self.emit(EV_ADMIN_OP_SCHEDULED, op_id, kind, eta);
+        op_id
     }
-}
 
-fn get_i64(key: i64) -> i64 {
-    let ctx = ctx();
-    let raw = unsafe { neo_storage_get(key, ctx) };
-    let null_probe = unsafe { neo_storage_get(NULL_PROBE_KEY, ctx) };
-    // Missing keys materialize as Null stack items; compare against a guaranteed-missing probe.
-    if raw == null_probe {
-        0
-    } else {
-        add1(raw) - 1
+    fn calc_constant(&self, slot: i64) -> i64 {
+        red_envelope_core::calc_constant(&self.rt, slot)
     }
-}
 
-fn exists(id: i64) -> bool {
-    env(P_CREATOR, id) != 0
-}
+    fn mint_token(&mut self, token_id: i64, owner: i64) {
+        self.put_i64(k2(P_TOKEN_OWNER, token_id), owner);
 
-fn token_exists(token_id: i64) -> bool {
-    get_i64(k2(P_TOKEN_OWNER, token_id)) != 0
-}
+        let bal_key = k2(P_OWNER_BALANCE, owner);
+        let bal = self.get_i64(bal_key);
+        self.put_i64(bal_key, bal.saturating_add(1));
 
-fn env(prefix: u8, id: i64) -> i64 {
-    get_i64(k2(prefix, id))
-}
+        self.put_key_i64(
+            K_TOTAL_SUPPLY,
+            self.get_key_i64(K_TOTAL_SUPPLY).saturating_add(1),
+        );
+    }
 
-fn set_env(prefix: u8, id: i64, v: i64) {
-    put_i64(k2(prefix, id), v)
-}
+    fn burn_token(&mut self, token_id: i64) {
+        let owner = self.get_i64(k2(P_TOKEN_OWNER, token_id));
+        if owner == 0 {
+            return;
+        }
+        self.put_i64(k2(P_TOKEN_OWNER, token_id), 0);
 
-fn alloc_id() -> i64 {
-    let id = get_key_i64(K_NEXT_ID).saturating_add(1);
-    put_key_i64(K_NEXT_ID, id);
-    id
-}
+        let bal_key = k2(P_OWNER_BALANCE, owner);
+        let bal = self.get_i64(bal_key);
+        if bal > 0 {
+            self.put_i64(bal_key, bal - 1);
+        }
 
-fn now_ms() -> i64 {
-    let t = get_key_i64(K_TIME_OVERRIDE);
-    if t > 0 {
-        return t;
+        let supply = self.get_key_i64(K_TOTAL_SUPPLY);
+        if supply > 0 {
+            self.put_key_i64(K_TOTAL_SUPPLY, supply - 1);
+        }
     }
 
-    #[cfg(target_arch = "wasm32")]
-    {
-        unsafe { neo_get_time() }
-    }
+    fn transfer_token(&mut self, token_id: i64, from: i64, to: i64) -> bool {
+        if from == 0 || to == 0 {
+            return false;
+        }
+        if self.get_i64(k2(P_TOKEN_OWNER, token_id)) != from {
+            return false;
+        }
 
-    #[cfg(not(target_arch = "wasm32"))]
-    {
-        0
-    }
-}
+        self.put_i64(k2(P_TOKEN_OWNER, token_id), to);
 
-fn is_paused_flag() -> bool {
-    get_key_i64(K_PAUSED) != 0
-}
+        let from_key = k2(P_OWNER_BALANCE, from);
+        let to_key = k2(P_OWNER_BALANCE, to);
 
-fn calc_packet(
-    remaining_amount: i64,
-    packets_left: i64,
-    neo_balance: i64,
-    total_amount: i64,
-    total_packets: i64,
-) -> i64 {
-    if remaining_amount <= 0 || packets_left <= 0 || total_amount <= 0 || total_packets <= 0 {
-        return 0;
-    }
+        let from_bal = self.get_i64(from_key);
+        if from_bal > 0 {
+            self.put_i64(from_key, from_bal - 1);
+        }
 
-    if packets_left == 1 {
-        return remaining_amount;
-    }
+        let to_bal = self.get_i64(to_key);
+        self.put_i64(to_key, to_bal.saturating_add(1));
 
-    let min_per_packet = MIN_PER_PACKET;
-    let feasible_max =
-        remaining_amount.saturating_sub((packets_left - 1).saturating_mul(min_per_packet));
-    if feasible_max <= min_per_packet {
-        return min_per_packet;
+        if self.env(P_TYPE, token_id) == ENVELOPE_TYPE_CLAIM {
+            self.unindex_holder_claim(from, token_id);
+            self.index_holder_claim(to, token_id);
+        }
+
+        true
     }
 
-    let dynamic_average = ceiling_div(remaining_amount, packets_left);
-    let lower_band_bps = volatility_lower_bps(total_packets);
-    let upper_band_bps = volatility_upper_bps(total_packets);
+    fn put_key_i64(&mut self, prefix: u8, v: i64) {
+        self.put_i64(key(prefix), v);
+    }
 
-    let mut min_for_this = dynamic_average.saturating_mul(lower_band_bps) / PERCENT_BASE;
-    if min_for_this < min_per_packet {
-        min_for_this = min_per_packet;
+    fn get_key_i64(&self, prefix: u8) -> i64 {
+        self.get_i64(key(prefix))
     }
 
-    let mut max_for_this =
-        ceiling_div(dynamic_average.saturating_mul(upper_band_bps), PERCENT_BASE);
+    fn put_i64(&mut self, key: i64, v: i64) {
+        self.rt.put(key, v);
+    }
 
-    let cap_by_percent = ceiling_div(
-        total_amount.saturating_mul(MAX_SINGLE_PACKET_BPS),
-        PERCENT_BASE,
-    );
-    let cap_by_average = ceiling_div(
-        dynamic_average.saturating_mul(MAX_SINGLE_PACKET_AVG_BPS),
-        PERCENT_BASE,
-    );
-    let mut hard_cap = cap_by_percent.max(cap_by_average);
-    if hard_cap < min_per_packet {
-        hard_cap = min_per_packet;
+    fn get_i64(&self, key: i64) -> i64 {
+        self.rt.get(key)
     }
 
-    if max_for_this > hard_cap {
-        max_for_this = hard_cap;
+    fn exists(&self, id: i64) -> bool {
+        red_envelope_core::keys::exists(&self.rt, id)
     }
-    if max_for_this > feasible_max {
-        max_for_this = feasible_max;
+
+    fn is_direct_user_invocation(&self) -> bool {
+        self.rt.is_direct_invocation()
     }
 
-    if min_for_this > max_for_this {
-        min_for_this = min_per_packet;
-        max_for_this = feasible_max;
+    fn is_contract_account(&self, account: i64) -> bool {
+        self.rt.is_contract_account(account)
     }
 
-    let range = max_for_this.saturating_sub(min_for_this).saturating_add(1);
-    if range <= 0 {
-        return min_per_packet.min(feasible_max);
+    fn is_frozen(&self, id: i64) -> bool {
+        red_envelope_core::keys::is_frozen(&self.rt, id)
     }
 
-    let entropy = runtime_entropy();
-    let mut divisor = 1i64;
+    fn token_exists(&self, token_id: i64) -> bool {
+        self.get_i64(k2(P_TOKEN_OWNER, token_id)) != 0
+    }
 
-    let roll1 = (entropy / divisor).rem_euclid(range);
-    divisor = mul_clamp_max(divisor, range);
-    let roll2 = (entropy / divisor).rem_euclid(range);
-    divisor = mul_clamp_max(divisor, range);
-    let mut best_roll = roll1.saturating_add(roll2) / 2;
+    fn env(&self, prefix: u8, id: i64) -> i64 {
+        self.get_i64(k2(prefix, id))
+    }
 
-    let mut extra_trials = 0;
-    if neo_balance >= 1000 {
-        extra_trials = 2;
-    } else if neo_balance >= 100 {
-        extra_trials = 1;
+    fn set_env(&mut self, prefix: u8, id: i64, v: i64) {
+        self.put_i64(k2(prefix, id), v)
     }
 
-    let mut i = 0;
-    while i < extra_trials {
-        let candidate1 = (entropy / divisor).rem_euclid(range);
-        divisor = mul_clamp_max(divisor, range);
-        let candidate2 = (entropy / divisor).rem_euclid(range);
-        divisor = mul_clamp_max(divisor, range);
+    fn index_creator_envelope(&mut self, creator: i64, id: i64) {
+        let count = self.get_i64(k2(I_CREATOR_COUNT, creator));
+        self.put_i64(k3(I_CREATOR_ENVELOPE, creator, count), id);
+        self.put_i64(k2(I_CREATOR_COUNT, creator), count.saturating_add(1));
+    }
 
-        let candidate_roll = candidate1.saturating_add(candidate2) / 2;
-        if candidate_roll > best_roll {
-            best_roll = candidate_roll;
+    fn release_pending_commit(&mut self, pool_id: i64) {
+        let pending = self.env(P_PENDING_COMMITS, pool_id);
+        if pending > 0 {
+            self.set_env(P_PENDING_COMMITS, pool_id, pending - 1);
         }
-        i += 1;
     }
 
-    let mut amount = min_for_this.saturating_add(best_roll);
-    if amount < min_per_packet {
-        amount = min_per_packet;
+    fn index_holder_claim(&mut self, holder: i64, claim_id: i64) {
+        let count = self.get_i64(k2(I_HOLDER_COUNT, holder));
+        self.put_i64(k3(I_HOLDER_CLAIM, holder, count), claim_id);
+        self.put_i64(k2(I_HOLDER_SLOT, claim_id), count.saturating_add(1));
+        self.put_i64(k2(I_HOLDER_COUNT, holder), count.saturating_add(1));
     }
-    if amount > feasible_max {
-        amount = feasible_max;
+
+    // Moves the holder's last claim into the vacated slot. Claims minted before the index
+    // existed have no slot and are left alone.
+    fn unindex_holder_claim(&mut self, holder: i64, claim_id: i64) {
+        let slot = self.get_i64(k2(I_HOLDER_SLOT, claim_id));
+        let count = self.get_i64(k2(I_HOLDER_COUNT, holder));
+        if slot <= 0 || slot > count {
+            return;
+        }
+
+        let last = self.get_i64(k3(I_HOLDER_CLAIM, holder, count - 1));
+        self.put_i64(k3(I_HOLDER_CLAIM, holder, slot - 1), last);
+        self.put_i64(k2(I_HOLDER_SLOT, last), slot);
+        self.put_i64(k3(I_HOLDER_CLAIM, holder, count - 1), 0);
+        self.put_i64(k2(I_HOLDER_SLOT, claim_id), 0);
+        self.put_i64(k2(I_HOLDER_COUNT, holder), count - 1);
     }
-    amount
-}
 
-fn runtime_entropy() -> i64 {
-    #[cfg(target_arch = "wasm32")]
-    {
-        let random = unsafe { neo_get_random() };
+    fn alloc_id(&mut self) -> i64 {
+        let id = self.get_key_i64(K_NEXT_ID).saturating_add(1);
+        self.put_key_i64(K_NEXT_ID, id);
+        id
+    }
 
-        let mut entropy = if random < 0 {
-            if random == i64::MIN {
-                i64::MAX
-            } else {
-                random.abs()
+    fn now_ms(&self) -> i64 {
+        #[cfg(feature = "testnet")]
+        {
+            let t = self.get_key_i64(K_TIME_OVERRIDE);
+            if t > 0 {
+                return t;
             }
-        } else {
-            random
-        };
-
-        if entropy == 0 {
-            entropy = 1;
         }
-        return entropy;
-    }
 
-    #[cfg(not(target_arch = "wasm32"))]
-    {
-        1
+        self.rt.now_ms()
     }
-}
 
-fn mul_clamp_max(a: i64, b: i64) -> i64 {
-    if a <= 0 || b <= 0 {
-        return 0;
+    fn has_witness(&self, account: i64) -> bool {
+        account != 0 && self.rt.check_witness(account)
     }
-    if a > i64::MAX / b {
-        i64::MAX
-    } else {
-        a * b
+
+    fn owner_witness(&self) -> bool {
+        self.has_witness(self.get_key_i64(K_OWNER))
     }
-}
 
-fn ceiling_div(numerator: i64, denominator: i64) -> i64 {
-    if denominator <= 0 || numerator <= 0 {
-        return 0;
+    fn emit(&mut self, event: i64, a: i64, b: i64, c: i64) {
+        self.rt.notify(event, a, b, c);
     }
-    numerator.saturating_add(denominator.saturating_sub(1)) / denominator
-}
 
-fn volatility_lower_bps(total_packets: i64) -> i64 {
-    if total_packets >= DENSE_PACKET_THRESHOLD {
-        DENSE_VOLATILITY_LOW_BPS
-    } else if total_packets >= MEDIUM_PACKET_THRESHOLD {
-        MEDIUM_VOLATILITY_LOW_BPS
-    } else {
-        SPARSE_VOLATILITY_LOW_BPS
+    fn is_paused_flag(&self) -> bool {
+        self.get_key_i64(K_PAUSED) != PAUSE_NONE
     }
-}
 
-fn volatility_upper_bps(total_packets: i64) -> i64 {
-    if total_packets >= DENSE_PACKET_THRESHOLD {
-        DENSE_VOLATILITY_HIGH_BPS
-    } else if total_packets >= MEDIUM_PACKET_THRESHOLD {
-        MEDIUM_VOLATILITY_HIGH_BPS
-    } else {
-        SPARSE_VOLATILITY_HIGH_BPS
+    // Withdraw-only pauses still let creators reclaim expired balances.
+    fn is_withdraw_blocked(&self) -> bool {
+        self.get_key_i64(K_PAUSED) == PAUSE_FULL
     }
 }
@@ -0,0 +1,1824 @@
+impl<R: red_envelope_core::Runtime> RedEnvelope<R> {
+    pub fn total_supply(&self) -> i64 {
+        self.get_key_i64(K_TOTAL_SUPPLY)
+    }
+
+    pub fn balance_of(&self, account: i64) -> i64 {
+        if account == 0 {
+            return 0;
+        }
+        self.get_i64(k2(P_OWNER_BALANCE, account))
+    }
+
+    pub fn owner_of(&self, token_id: i64) -> i64 {
+        self.get_i64(k2(P_TOKEN_OWNER, token_id))
+    }
+
+    pub fn properties(&self, token_id: i64) -> i64 {
+        if !self.token_exists(token_id) {
+            return 0;
+        }
+        self.env(P_TYPE, token_id)
+    }
+
+    pub fn tokens(&self) -> i64 {
+        self.total_supply()
+    }
+
+    pub fn tokens_of(&self, owner: i64) -> i64 {
+        self.balance_of(owner)
+    }
+
+    pub fn get_owner(&self) -> i64 {
+        self.get_key_i64(K_OWNER)
+    }
+
+    pub fn set_owner(&mut self, new_owner: i64) {
+        // The timelocked admin operations all trust this key, so only the current owner moves it.
+        if new_owner == 0 || !self.owner_witness() {
+            return;
+        }
+        self.put_key_i64(K_OWNER, new_owner);
+    }
+
+    pub fn is_owner(&self) -> bool {
+        self.get_key_i64(K_OWNER) != 0
+    }
+
+    pub fn verify(&self) -> bool {
+        self.is_owner()
+    }
+
+    // Void in the C# ABI, so a caller without the owner's witness faults instead of reading `false`.
+    pub fn pause(&mut self) {
+        if !self.set_pause_level(PAUSE_FULL) {
+            self.rt.abort();
+        }
+    }
+
+    pub fn resume(&mut self) {
+        if !self.set_pause_level(PAUSE_NONE) {
+            self.rt.abort();
+        }
+    }
+
+    pub fn set_pause_level(&mut self, level: i64) -> bool {
+        if !self.owner_witness() || !(PAUSE_NONE..=PAUSE_WITHDRAW_ONLY).contains(&level) {
+            return false;
+        }
+        let previous = self.get_key_i64(K_PAUSED);
+        self.put_key_i64(K_PAUSED, level);
+        self.emit(EV_PAUSE_LEVEL_CHANGED, level, previous, self.now_ms());
+        true
+    }
+
+    pub fn get_pause_level(&self) -> i64 {
+        self.get_key_i64(K_PAUSED)
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.get_key_i64(K_PAUSED) != 0
+    }
+
+    pub fn schedule_upgrade(&mut self, nef: i64, manifest: i64) -> i64 {
+        let op_id = self.schedule_admin_op(ADMIN_OP_UPGRADE, nef, manifest);
+        if op_id != 0 {
+            self.set_env(A_NEF_HASH, op_id, self.rt.sha256_raw(nef));
+            self.set_env(A_MANIFEST_HASH, op_id, self.rt.sha256_raw(manifest));
+        }
+        op_id
+    }
+
+    pub fn cancel_admin_operation(&mut self, op_id: i64) -> bool {
+        if !self.owner_witness() || self.env(A_STATE, op_id) != ADMIN_OP_QUEUED {
+            return false;
+        }
+
+        self.set_env(A_STATE, op_id, ADMIN_OP_CANCELLED);
+        self.emit(
+            EV_ADMIN_OP_CANCELLED,
+            op_id,
+            self.env(A_KIND, op_id),
+            self.env(A_ETA, op_id),
+        );
+        true
+    }
+
+    pub fn execute_admin_operation(&mut self, op_id: i64) -> bool {
+        if !self.owner_witness()
+            || self.env(A_STATE, op_id) != ADMIN_OP_QUEUED
+            || self.now_ms() < self.env(A_ETA, op_id)
+        {
+            return false;
+        }
+
+        let kind = self.env(A_KIND, op_id);
+        self.set_env(A_STATE, op_id, ADMIN_OP_EXECUTED);
+        self.emit(EV_ADMIN_OP_EXECUTED, op_id, kind, self.now_ms());
+
+        if kind == ADMIN_OP_UPGRADE {
+            // NEF and manifest were stored as raw ByteStrings; never read them as integers.
+            let nef = self.rt.get_raw(k2(A_ARG0, op_id));
+            let manifest = self.rt.get_raw(k2(A_ARG1, op_id));
+            self.rt.update_contract(nef, manifest);
+        } else if kind == ADMIN_OP_SET_CONSTANTS {
+            let mut slot = 1;
+            while slot <= CONST_COUNT {
+                self.set_env(C_VALUE, slot, self.get_i64(k3(C_PENDING, op_id, slot)));
+                slot += 1;
+            }
+        }
+        true
+    }
+
+    #[cfg(feature = "testnet")]
+    pub fn set_time_override(&mut self, timestamp_ms: i64) -> bool {
+        if !self.owner_witness() || timestamp_ms <= 0 {
+            return false;
+        }
+        self.put_key_i64(K_TIME_OVERRIDE, timestamp_ms);
+        true
+    }
+
+    #[cfg(feature = "testnet")]
+    pub fn clear_time_override(&mut self) -> bool {
+        if !self.owner_witness() {
+            return false;
+        }
+        self.put_key_i64(K_TIME_OVERRIDE, 0);
+        true
+    }
+
+    pub fn get_admin_operation(&self, op_id: i64) -> i64 {
+        self.env(A_STATE, op_id)
+    }
+
+    pub fn get_admin_operation_eta(&self, op_id: i64) -> i64 {
+        self.env(A_ETA, op_id)
+    }
+
+    pub fn get_admin_operation_kind(&self, op_id: i64) -> i64 {
+        self.env(A_KIND, op_id)
+    }
+
+    // The value a queued `setCalculationConstants` op writes to `slot`, or 0 for other ops.
+    pub fn get_admin_operation_constant(&self, op_id: i64, slot: i64) -> i64 {
+        if self.env(A_KIND, op_id) != ADMIN_OP_SET_CONSTANTS || !(1..=CONST_COUNT).contains(&slot) {
+            return 0;
+        }
+        self.get_i64(k3(C_PENDING, op_id, slot))
+    }
+
+    pub fn get_admin_operation_nef_hash(&self, op_id: i64) -> i64 {
+        self.env(A_NEF_HASH, op_id)
+    }
+
+    pub fn get_admin_operation_manifest_hash(&self, op_id: i64) -> i64 {
+        self.env(A_MANIFEST_HASH, op_id)
+    }
+
+    pub fn contract_deploy(&mut self, data: i64, update: bool) {
+        if !update {
+            self.put_key_i64(K_OWNER, data);
+            self.put_key_i64(K_NEXT_ID, 0);
+            self.put_key_i64(K_TOTAL_ENVELOPES, 0);
+            self.put_key_i64(K_TOTAL_DISTRIBUTED, 0);
+            #[cfg(feature = "testnet")]
+            self.put_key_i64(K_TIME_OVERRIDE, 0);
+            self.put_key_i64(K_TOTAL_SUPPLY, 0);
+            self.put_key_i64(K_PAUSED, 0);
+        } else if self.get_key_i64(K_OWNER) == 0 {
+            self.put_key_i64(K_OWNER, data);
+        }
+    }
+
+    pub fn on_nep17_payment(&mut self, from: i64, amount: i64, data: i64) {
+        // Anything but GAS or a listed token faults, so the sender keeps its tokens.
+        let caller = self.rt.calling_contract();
+        let pay_token = if caller == GAS_TOKEN {
+            0
+        } else if self.env(T_MIN_AMOUNT, caller) > 0 {
+            caller
+        } else {
+            self.rt.abort()
+        };
+
+        if from == 0 {
+            return;
+        }
+
+        if let Some(top_up) = red_envelope_core::decode_top_up(data) {
+            // A refused or paused top-up faults so the deposit goes back to the sender.
+            if self.is_paused_flag() || !self.top_up_envelope(from, amount, pay_token, top_up) {
+                self.rt.abort();
+            }
+            return;
+        }
+
+        let default_expiry_ms = DEFAULT_EXPIRY_MS.min(self.calc_constant(CONST_MAX_EXPIRY_MS));
+        if let Some(batch) = red_envelope_core::decode_batch(data, default_expiry_ms) {
+            // So does a paused batch or one that creates nothing.
+            if self.is_paused_flag()
+                || self.create_envelope_batch(from, amount, pay_token, batch) == 0
+            {
+                self.rt.abort();
+            }
+            return;
+        }
+
+        if self.is_paused_flag() {
+            return;
+        }
+
+        if amount < red_envelope_core::token_min_amount(&self.rt, pay_token) {
+            return;
+        }
+
+        let terms = red_envelope_core::decode_payment(data, default_expiry_ms);
+        let packet_count = terms.packet_count;
+        let envelope_type = terms.envelope_type;
+
+        if packet_count <= 0
+            || packet_count > self.calc_constant(CONST_MAX_PACKETS)
+            || amount
+                < packet_count
+                    .saturating_mul(red_envelope_core::token_min_per_packet(&self.rt, pay_token))
+            || !(envelope_type == ENVELOPE_TYPE_SPREADING
+                || envelope_type == ENVELOPE_TYPE_POOL
+                || envelope_type == ENVELOPE_TYPE_EQUAL)
+        {
+            return;
+        }
+
+        let _ = self.create_envelope(
+            from,
+            amount,
+            packet_count,
+            terms.expiry_ms,
+            envelope_type,
+            terms.flags,
+            pay_token,
+            terms.start_delay_ms,
+            true,
+        );
+    }
+
+    pub fn token_uri(&self, token_id: i64) -> i64 {
+        if self.token_exists(token_id) {
+            token_id
+        } else {
+            0
+        }
+    }
+
+    pub fn calculate_packet_amount(&self, envelope_id: i64, packet_index: i64) -> i64 {
+        if !self.exists(envelope_id) {
+            return 0;
+        }
+
+        let packet = self.env(P_PACKET, envelope_id);
+        let remaining = self.env(P_REMAINING, envelope_id);
+        let remaining_packets = packet.saturating_sub(packet_index);
+
+        if remaining <= 0 || remaining_packets <= 0 {
+            return 0;
+        }
+
+        self.packet_amount(envelope_id, remaining, remaining_packets, self.rt.entropy())
+    }
+
+    pub fn claim_from_pool(&mut self, pool_id: i64, claimer: i64) -> i64 {
+        if self.is_secret_pool(pool_id) {
+            return 0;
+        }
+
+        self.claim_pool_slot(pool_id, claimer)
+    }
+
+    // The toolchain only injects witness guards for the original entry points, so check here.
+    pub fn claim_from_pool_with_code(&mut self, pool_id: i64, claimer: i64, code: i64) -> i64 {
+        if !self.is_secret_pool(pool_id)
+            || !self.has_witness(claimer)
+            || self.check_code_eligibility(pool_id, claimer, code) != ELIGIBILITY_OK
+        {
+            return 0;
+        }
+
+        self.claim_pool_slot(pool_id, claimer)
+    }
+
+    pub fn set_pool_secret(&mut self, pool_id: i64, creator: i64, commitment: i64) -> bool {
+        if commitment == 0
+            || !self.can_set_pool_gate(pool_id, creator, ENVELOPE_FLAG_SECRET, P_SECRET_HASH)
+        {
+            return false;
+        }
+
+        self.set_env(P_SECRET_HASH, pool_id, commitment);
+        true
+    }
+
+    pub fn set_pool_allowlist(&mut self, pool_id: i64, creator: i64, root: i64) -> bool {
+        if root == 0
+            || !self.can_set_pool_gate(pool_id, creator, ENVELOPE_FLAG_ALLOWLIST, P_ALLOWLIST_ROOT)
+        {
+            return false;
+        }
+
+        self.set_env(P_ALLOWLIST_ROOT, pool_id, root);
+        true
+    }
+
+    // Folds one proof sibling into the claimer's node; a zero sibling restarts from the leaf.
+    // Callers batch every sibling plus the claim into one transaction script.
+    pub fn prove_allowlist(&mut self, pool_id: i64, claimer: i64, sibling: i64) -> i64 {
+        if self.is_paused_flag()
+            || !self.has_witness(claimer)
+            || !self.is_direct_user_invocation()
+            || self.env(P_FLAGS, pool_id) & ENVELOPE_FLAG_ALLOWLIST == 0
+        {
+            return 0;
+        }
+
+        let node_key = k3(P_ALLOWLIST_NODE, pool_id, claimer);
+        if sibling == 0 {
+            self.put_i64(node_key, 0);
+            return 0;
+        }
+
+        let mut node = self.get_i64(node_key);
+        if node == 0 {
+            node = red_envelope_core::allowlist_leaf(&self.rt, claimer);
+        }
+        let node = red_envelope_core::merkle_parent(&self.rt, node, sibling);
+        self.put_i64(node_key, node);
+        node
+    }
+
+    pub fn check_code_eligibility(&self, pool_id: i64, user: i64, code: i64) -> i64 {
+        red_envelope_core::code_eligibility_status(&self.rt, pool_id, user, code, self.now_ms())
+    }
+
+    pub fn commit_pool_claim(&mut self, pool_id: i64, claimer: i64) -> bool {
+        if self.is_paused_flag()
+            || claimer == 0
+            || !self.has_witness(claimer)
+            || !self.is_direct_user_invocation()
+            || self.is_contract_account(claimer)
+            || !self.exists(pool_id)
+            || self.is_frozen(pool_id)
+            || self.env(P_TYPE, pool_id) != ENVELOPE_TYPE_POOL
+            || !self.is_commit_reveal(pool_id)
+            || self.env(P_ACTIVE, pool_id) == 0
+            || self
+                .env(P_OPENED, pool_id)
+                .saturating_add(self.env(P_PENDING_COMMITS, pool_id))
+                >= self.env(P_PACKET, pool_id)
+            || !self.is_started(pool_id)
+            || self.now_ms() > self.env(P_EXPIRY, pool_id)
+            || self.get_i64(k3(P_COMMIT_STATE, pool_id, claimer)) != 0
+            || !self.allowlist_proven(pool_id, claimer)
+        {
+            return false;
+        }
+
+        let now = self.now_ms();
+        self.put_i64(k3(P_COMMIT_STATE, pool_id, claimer), COMMIT_PENDING);
+        self.put_i64(k3(P_COMMIT_AT, pool_id, claimer), now);
+        self.put_i64(
+            k3(P_COMMIT_BLOCK, pool_id, claimer),
+            self.rt.block_index().saturating_add(1),
+        );
+        self.set_env(
+            P_PENDING_COMMITS,
+            pool_id,
+            self.env(P_PENDING_COMMITS, pool_id).saturating_add(1),
+        );
+
+        self.emit(EV_CLAIM_COMMITTED, pool_id, claimer, now);
+        true
+    }
+
+    // Anyone may reveal, so a claimer cannot discard an unfavourable roll by staying silent.
+    pub fn reveal_pool_claim(&mut self, pool_id: i64, claimer: i64) -> i64 {
+        let committed_at = self.get_i64(k3(P_COMMIT_AT, pool_id, claimer));
+        let seed_block = self
+            .get_i64(k3(P_COMMIT_BLOCK, pool_id, claimer))
+            .saturating_add(1);
+        let now = self.now_ms();
+        if self.is_paused_flag()
+            || self.is_frozen(pool_id)
+            || self.get_i64(k3(P_COMMIT_STATE, pool_id, claimer)) != COMMIT_PENDING
+            || self.rt.block_index() < seed_block
+            || now > committed_at.saturating_add(COMMIT_REVEAL_WINDOW_MS)
+            || now > self.env(P_EXPIRY, pool_id)
+        {
+            return 0;
+        }
+
+        // The roll is seeded by the nonce of the block after the commit, unknown when committing and
+        // fixed afterwards, so retrying the reveal in another transaction cannot reroll it.
+        let nonce = self.rt.block_nonce(seed_block);
+        let seed = self
+            .rt
+            .sha256_pair(claimer, self.rt.sha256_pair(pool_id, nonce));
+        // A failed mint leaves the commit pending, so its slot stays reserved until it expires.
+        let claim_id = self.mint_pool_claim(pool_id, claimer, seed);
+        if claim_id == 0 {
+            return 0;
+        }
+
+        self.release_pending_commit(pool_id);
+        self.put_i64(k3(P_COMMIT_STATE, pool_id, claimer), COMMIT_REVEALED);
+        self.emit(EV_CLAIM_REVEALED, pool_id, claimer, claim_id);
+        claim_id
+    }
+
+    // Abandoned commits release their reserved slot; the claimer forfeits this pool.
+    pub fn expire_pool_commit(&mut self, pool_id: i64, claimer: i64) -> bool {
+        let committed_at = self.get_i64(k3(P_COMMIT_AT, pool_id, claimer));
+        let now = self.now_ms();
+        if self.get_i64(k3(P_COMMIT_STATE, pool_id, claimer)) != COMMIT_PENDING
+            || (now <= committed_at.saturating_add(COMMIT_REVEAL_WINDOW_MS)
+                && now <= self.env(P_EXPIRY, pool_id))
+        {
+            return false;
+        }
+
+        self.put_i64(k3(P_COMMIT_STATE, pool_id, claimer), COMMIT_EXPIRED);
+        self.release_pending_commit(pool_id);
+
+        self.emit(EV_COMMIT_EXPIRED, pool_id, claimer, committed_at);
+        true
+    }
+
+    pub fn get_pool_commit_state(&self, pool_id: i64, claimer: i64) -> i64 {
+        self.get_i64(k3(P_COMMIT_STATE, pool_id, claimer))
+    }
+
+    pub fn open_claim(&mut self, claim_id: i64, opener: i64) -> i64 {
+        if !self.claim_openable(claim_id, opener) {
+            return 0;
+        }
+
+        let amount = self.env(P_REMAINING, claim_id);
+        self.set_env(P_OPENED, claim_id, 1);
+        self.set_env(P_REMAINING, claim_id, 0);
+        self.set_env(P_ACTIVE, claim_id, 0);
+        amount
+    }
+
+    // Zero ids are padding. Each id emits `BatchItemResult(index, id, amount)`; returns the GAS
+    // total opened so the caller settles one GAS payout. Token claims open too, and their results
+    // are paid per item in `getEnvelopeToken(id)`. The toolchain's witness guard skips batch entries.
+    pub fn open_claims(&mut self, opener: i64, strict: bool, claim_ids: &[i64]) -> i64 {
+        if !self.has_witness(opener)
+            || (strict && !self.batch_ready(claim_ids, |c, id| c.claim_openable(id, opener)))
+        {
+            return 0;
+        }
+
+        let mut total = 0i64;
+        for (index, &claim_id) in claim_ids.iter().enumerate() {
+            if claim_id == 0 {
+                continue;
+            }
+            let amount = self.open_claim(claim_id, opener);
+            self.emit(EV_BATCH_ITEM_RESULT, index as i64, claim_id, amount);
+            if self.env(P_PAY_TOKEN, claim_id) == 0 {
+                total = total.saturating_add(amount);
+            }
+        }
+        total
+    }
+
+    // Like `openClaims`, but results carry the minted claim id and the return value counts them;
+    // nothing is paid until the claims are opened.
+    pub fn claim_from_pools(&mut self, claimer: i64, strict: bool, pool_ids: &[i64]) -> i64 {
+        if !self.has_witness(claimer)
+            || (strict
+                && !self.batch_ready(pool_ids, |c, id| {
+                    !c.is_secret_pool(id) && c.pool_claimable(id, claimer)
+                }))
+        {
+            return 0;
+        }
+
+        let mut minted = 0i64;
+        for (index, &pool_id) in pool_ids.iter().enumerate() {
+            if pool_id == 0 {
+                continue;
+            }
+            let claim_id = self.claim_from_pool(pool_id, claimer);
+            self.emit(EV_BATCH_ITEM_RESULT, index as i64, pool_id, claim_id);
+            if claim_id != 0 {
+                minted += 1;
+            }
+        }
+        minted
+    }
+
+    pub fn transfer_claim(&mut self, claim_id: i64, from: i64, to: i64) {
+        if self.is_paused_flag()
+            || from == 0
+            || to == 0
+            || !self.is_direct_user_invocation()
+            || self.is_contract_account(to)
+            || !self.exists(claim_id)
+            || self.is_frozen(claim_id)
+            || self.env(P_TYPE, claim_id) != ENVELOPE_TYPE_CLAIM
+            || self.get_i64(k2(P_TOKEN_OWNER, claim_id)) != from
+        {
+            return;
+        }
+
+        self.transfer_token(claim_id, from, to);
+    }
+
+    pub fn reclaim_pool(&mut self, pool_id: i64, creator: i64) -> i64 {
+        if self.is_withdraw_blocked()
+            || creator == 0
+            || !self.is_direct_user_invocation()
+            || !self.exists(pool_id)
+            || self.is_frozen(pool_id)
+            || !self.is_pool_type(pool_id)
+            || self.env(P_CREATOR, pool_id) != creator
+            || self.now_ms() <= self.env(P_EXPIRY, pool_id)
+        {
+            return 0;
+        }
+
+        let mut refund = self.env(P_REMAINING, pool_id);
+        let opened = self.env(P_OPENED, pool_id);
+        let mut i = 1;
+        while i <= opened {
+            let claim_id = self.get_i64(k3(P_POOL_CLAIM_INDEX, pool_id, i));
+            if claim_id > 0
+                && self.env(P_TYPE, claim_id) == ENVELOPE_TYPE_CLAIM
+                && self.env(P_ACTIVE, claim_id) != 0
+                && !self.is_frozen(claim_id)
+            {
+                let rem = self.env(P_REMAINING, claim_id);
+                if rem > 0 {
+                    refund = refund.saturating_add(rem);
+                    self.set_env(P_REMAINING, claim_id, 0);
+                    self.set_env(P_ACTIVE, claim_id, 0);
+                }
+            }
+            i += 1;
+        }
+
+        if refund <= 0 {
+            return 0;
+        }
+
+        self.set_env(P_REMAINING, pool_id, 0);
+        self.set_env(P_ACTIVE, pool_id, 0);
+        self.emit(EV_ENVELOPE_REFUNDED, pool_id, creator, refund);
+        refund
+    }
+
+    pub fn freeze(&mut self, id: i64) -> bool {
+        if !self.owner_witness() || !self.exists(id) || self.is_frozen(id) {
+            return false;
+        }
+        self.set_env(P_FROZEN, id, 1);
+        self.emit(EV_ENVELOPE_FROZEN, id, self.env(P_TYPE, id), self.now_ms());
+        true
+    }
+
+    pub fn unfreeze(&mut self, id: i64) -> bool {
+        if !self.owner_witness() || !self.is_frozen(id) {
+            return false;
+        }
+        self.set_env(P_FROZEN, id, 0);
+        self.emit(EV_ENVELOPE_UNFROZEN, id, self.env(P_TYPE, id), self.now_ms());
+        true
+    }
+
+    pub fn is_frozen_id(&self, id: i64) -> bool {
+        self.is_frozen(id)
+    }
+
+    // `[id, creator, totalAmount, packetCount, openedCount, remainingAmount, active, expiryTime,
+    // envelopeType, parentEnvelopeId, currentHolder, luckiestClaimer, luckiestAmount]`; empty for
+    // an unknown id.
+    pub fn get_envelope_state(&mut self, envelope_id: i64) -> i64 {
+        let state = self.rt.array_new();
+        if !self.exists(envelope_id) {
+            return state;
+        }
+        for value in [
+            envelope_id,
+            self.env(P_CREATOR, envelope_id),
+            self.env(P_TOTAL, envelope_id),
+            self.env(P_PACKET, envelope_id),
+            self.env(P_OPENED, envelope_id),
+            self.env(P_REMAINING, envelope_id),
+            self.env(P_ACTIVE, envelope_id),
+            self.env(P_EXPIRY, envelope_id),
+            self.env(P_TYPE, envelope_id),
+            self.env(P_PARENT, envelope_id),
+            self.get_i64(k2(P_TOKEN_OWNER, envelope_id)),
+            self.env(P_LUCKIEST_HOLDER, envelope_id),
+            self.env(P_LUCKIEST_AMOUNT, envelope_id),
+        ] {
+            self.rt.array_append(state, value);
+        }
+        state
+    }
+
+    pub fn get_claim_state(&self, claim_id: i64) -> i64 {
+        if !self.exists(claim_id) || self.env(P_TYPE, claim_id) != ENVELOPE_TYPE_CLAIM {
+            return 0;
+        }
+        self.env(P_REMAINING, claim_id)
+    }
+
+    pub fn check_eligibility(&self, envelope_id: i64, user: i64) -> i64 {
+        self.eligibility_status(envelope_id, user, false)
+    }
+
+    pub fn check_open_eligibility(&self, envelope_id: i64, user: i64) -> i64 {
+        self.eligibility_status(envelope_id, user, true)
+    }
+
+    pub fn has_opened(&self, envelope_id: i64, opener: i64) -> bool {
+        self.get_i64(k3(P_OPENED_AMOUNT, envelope_id, opener)) > 0
+    }
+
+    pub fn get_opened_amount(&self, envelope_id: i64, opener: i64) -> i64 {
+        self.get_i64(k3(P_OPENED_AMOUNT, envelope_id, opener))
+    }
+
+    pub fn has_claimed_from_pool(&self, pool_id: i64, claimer: i64) -> bool {
+        self.get_i64(k3(P_POOL_CLAIMED, pool_id, claimer)) > 0
+    }
+
+    pub fn get_pool_claimed_amount(&self, pool_id: i64, claimer: i64) -> i64 {
+        self.get_i64(k3(P_POOL_CLAIMED, pool_id, claimer))
+    }
+
+    // Live values in `CONST_*` slot order, `MIN_AMOUNT` first.
+    pub fn get_calculation_constants(&mut self) -> i64 {
+        let values = self.rt.array_new();
+        let mut slot = 1;
+        while slot <= CONST_COUNT {
+            let value = self.calc_constant(slot);
+            self.rt.array_append(values, value);
+            slot += 1;
+        }
+        values
+    }
+
+    pub fn get_calculation_constant(&self, slot: i64) -> i64 {
+        self.calc_constant(slot)
+    }
+
+    // Changes are staged under a timelocked admin operation and applied by `executeAdminOperation`.
+    #[allow(clippy::too_many_arguments)]
+    pub fn set_calculation_constants(
+        &mut self,
+        min_amount: i64,
+        max_packets: i64,
+        min_per_packet: i64,
+        dense_low_bps: i64,
+        dense_high_bps: i64,
+        medium_low_bps: i64,
+        medium_high_bps: i64,
+        sparse_low_bps: i64,
+        sparse_high_bps: i64,
+        max_expiry_ms: i64,
+    ) -> i64 {
+        if min_per_packet <= 0
+            || min_amount < min_per_packet
+            || max_packets <= 0
+            || max_packets > MAX_PACKETS_UPPER_BOUND
+            || !volatility_band_valid(dense_low_bps, dense_high_bps)
+            || !volatility_band_valid(medium_low_bps, medium_high_bps)
+            || !volatility_band_valid(sparse_low_bps, sparse_high_bps)
+            || max_expiry_ms <= 0
+            || max_expiry_ms > MAX_EXPIRY_UPPER_BOUND_MS
+        {
+            return 0;
+        }
+
+        let op_id = self.schedule_admin_op(ADMIN_OP_SET_CONSTANTS, 0, 0);
+        if op_id == 0 {
+            return 0;
+        }
+
+        self.put_i64(k3(C_PENDING, op_id, CONST_MIN_AMOUNT), min_amount);
+        self.put_i64(k3(C_PENDING, op_id, CONST_MAX_PACKETS), max_packets);
+        self.put_i64(k3(C_PENDING, op_id, CONST_MIN_PER_PACKET), min_per_packet);
+        self.put_i64(k3(C_PENDING, op_id, CONST_DENSE_LOW_BPS), dense_low_bps);
+        self.put_i64(k3(C_PENDING, op_id, CONST_DENSE_HIGH_BPS), dense_high_bps);
+        self.put_i64(k3(C_PENDING, op_id, CONST_MEDIUM_LOW_BPS), medium_low_bps);
+        self.put_i64(k3(C_PENDING, op_id, CONST_MEDIUM_HIGH_BPS), medium_high_bps);
+        self.put_i64(k3(C_PENDING, op_id, CONST_SPARSE_LOW_BPS), sparse_low_bps);
+        self.put_i64(k3(C_PENDING, op_id, CONST_SPARSE_HIGH_BPS), sparse_high_bps);
+        self.put_i64(k3(C_PENDING, op_id, CONST_MAX_EXPIRY_MS), max_expiry_ms);
+        op_id
+    }
+
+    // Listing (or relisting) sets both minimums; `min_amount == 0` delists but keeps the
+    // per-packet floor so envelopes already denominated in the token still settle.
+    pub fn set_token_config(&mut self, token: i64, min_amount: i64, min_per_packet: i64) -> bool {
+        if !self.owner_witness()
+            || token == 0
+            || token == GAS_TOKEN
+            || (min_amount != 0 && (min_per_packet <= 0 || min_amount < min_per_packet))
+        {
+            return false;
+        }
+
+        self.set_env(T_MIN_AMOUNT, token, min_amount);
+        if min_amount != 0 {
+            self.set_env(T_MIN_PER_PACKET, token, min_per_packet);
+        }
+        self.emit(EV_TOKEN_CONFIGURED, token, min_amount, min_per_packet);
+        true
+    }
+
+    pub fn get_token_min_amount(&self, token: i64) -> i64 {
+        self.env(T_MIN_AMOUNT, token)
+    }
+
+    pub fn get_token_min_per_packet(&self, token: i64) -> i64 {
+        self.env(T_MIN_PER_PACKET, token)
+    }
+
+    pub fn get_envelope_token(&self, envelope_id: i64) -> i64 {
+        self.env(P_PAY_TOKEN, envelope_id)
+    }
+
+    pub fn get_total_envelopes(&self) -> i64 {
+        self.get_key_i64(K_TOTAL_ENVELOPES)
+    }
+
+    pub fn get_total_distributed(&self) -> i64 {
+        self.get_key_i64(K_TOTAL_DISTRIBUTED)
+    }
+
+    pub fn get_pool_claim_id_by_index(&self, pool_id: i64, claim_index: i64) -> i64 {
+        self.get_i64(k3(P_POOL_CLAIM_INDEX, pool_id, claim_index))
+    }
+
+    pub fn get_pool_claim_count(&self, pool_id: i64) -> i64 {
+        if !self.is_pool_type(pool_id) {
+            return 0;
+        }
+        red_envelope_core::pool_claim_count(&self.rt, pool_id)
+    }
+
+    pub fn get_pool_claimer(&self, pool_id: i64, claim_index: i64) -> i64 {
+        red_envelope_core::pool_claim_record(&self.rt, pool_id, claim_index).claimer
+    }
+
+    // Rows are `[claimIndex, claimId, claimer, amount, opened]`; `offset` 0 is claim index 1.
+    pub fn get_pool_claims(&mut self, pool_id: i64, offset: i64, limit: i64) -> i64 {
+        let count = self.get_pool_claim_count(pool_id);
+        let rows = self.rt.array_new();
+        for index in red_envelope_core::page_range(count, offset, limit.min(MAX_PAGE_SIZE)) {
+            let record = red_envelope_core::pool_claim_record(&self.rt, pool_id, index + 1);
+            let row = self.rt.array_new();
+            for value in [
+                record.claim_index,
+                record.claim_id,
+                record.claimer,
+                record.amount,
+                record.opened as i64,
+            ] {
+                self.rt.array_append(row, value);
+            }
+            self.rt.array_append(rows, row);
+        }
+        rows
+    }
+
+    pub fn is_claim_opened(&self, claim_id: i64) -> bool {
+        self.env(P_TYPE, claim_id) == ENVELOPE_TYPE_CLAIM && self.env(P_OPENED, claim_id) != 0
+    }
+
+    pub fn get_luckiest_claimer(&self, envelope_id: i64) -> i64 {
+        self.env(P_LUCKIEST_HOLDER, envelope_id)
+    }
+
+    pub fn get_luckiest_amount(&self, envelope_id: i64) -> i64 {
+        self.env(P_LUCKIEST_AMOUNT, envelope_id)
+    }
+
+    pub fn get_creator_envelope_count(&self, creator: i64) -> i64 {
+        red_envelope_core::creator_envelope_count(&self.rt, creator)
+    }
+
+    pub fn get_envelope_by_creator_index(&self, creator: i64, index: i64) -> i64 {
+        red_envelope_core::creator_envelope_at(&self.rt, creator, index)
+    }
+
+    pub fn get_holder_claim_count(&self, holder: i64) -> i64 {
+        red_envelope_core::holder_claim_count(&self.rt, holder)
+    }
+
+    pub fn get_claim_by_holder_index(&self, holder: i64, index: i64) -> i64 {
+        red_envelope_core::holder_claim_at(&self.rt, holder, index)
+    }
+
+    // Paged reads return at most `MAX_PAGE_SIZE` ids, in index order.
+    pub fn get_envelopes_by_creator(&mut self, creator: i64, offset: i64, limit: i64) -> i64 {
+        let count = red_envelope_core::creator_envelope_count(&self.rt, creator);
+        let ids = self.rt.array_new();
+        for index in red_envelope_core::page_range(count, offset, limit.min(MAX_PAGE_SIZE)) {
+            let id = red_envelope_core::creator_envelope_at(&self.rt, creator, index);
+            self.rt.array_append(ids, id);
+        }
+        ids
+    }
+
+    pub fn get_claims_by_holder(&mut self, holder: i64, offset: i64, limit: i64) -> i64 {
+        let count = red_envelope_core::holder_claim_count(&self.rt, holder);
+        let ids = self.rt.array_new();
+        for index in red_envelope_core::page_range(count, offset, limit.min(MAX_PAGE_SIZE)) {
+            let id = red_envelope_core::holder_claim_at(&self.rt, holder, index);
+            self.rt.array_append(ids, id);
+        }
+        ids
+    }
+
+    pub fn transfer(&mut self, to: i64, token_id: i64, _data: i64) -> bool {
+        if self.is_paused_flag()
+            || to == 0
+            || !self.is_direct_user_invocation()
+            || self.is_contract_account(to)
+            || !self.token_exists(token_id)
+            || self.is_frozen(token_id)
+            || self.is_recipient_locked(token_id)
+        {
+            return false;
+        }
+
+        let t = self.env(P_TYPE, token_id);
+        if !(t == ENVELOPE_TYPE_SPREADING || t == ENVELOPE_TYPE_CLAIM) {
+            return false;
+        }
+
+        let from = self.get_i64(k2(P_TOKEN_OWNER, token_id));
+        if !self.has_witness(from) {
+            return false;
+        }
+
+        self.transfer_token(token_id, from, to)
+    }
+
+    pub fn open_envelope(&mut self, envelope_id: i64, opener: i64) -> i64 {
+        if self.is_paused_flag()
+            || opener == 0
+            || !self.is_direct_user_invocation()
+            || self.is_contract_account(opener)
+            || !self.exists(envelope_id)
+            || self.is_frozen(envelope_id)
+            || self.env(P_TYPE, envelope_id) != ENVELOPE_TYPE_SPREADING
+            || self.env(P_ACTIVE, envelope_id) == 0
+            || self.env(P_OPENED, envelope_id) >= self.env(P_PACKET, envelope_id)
+            || !self.is_started(envelope_id)
+            || self.now_ms() > self.env(P_EXPIRY, envelope_id)
+            || self.get_i64(k2(P_TOKEN_OWNER, envelope_id)) != opener
+            || self.get_i64(k3(P_OPENED_AMOUNT, envelope_id, opener)) > 0
+        {
+            return 0;
+        }
+
+        let remaining = self.env(P_REMAINING, envelope_id);
+        let opened = self.env(P_OPENED, envelope_id);
+        let packet = self.env(P_PACKET, envelope_id);
+        let amount = self.packet_amount(
+            envelope_id,
+            remaining,
+            packet.saturating_sub(opened),
+            self.rt.entropy(),
+        );
+        if amount <= 0 || amount > remaining {
+            return 0;
+        }
+
+        self.put_i64(k3(P_OPENED_AMOUNT, envelope_id, opener), amount);
+        self.set_env(P_OPENED, envelope_id, opened.saturating_add(1));
+
+        let rem2 = remaining.saturating_sub(amount);
+        self.set_env(P_REMAINING, envelope_id, rem2);
+        let depleted = rem2 == 0 || opened.saturating_add(1) >= packet;
+        if depleted {
+            self.set_env(P_ACTIVE, envelope_id, 0);
+        }
+        self.record_luckiest(envelope_id, opener, amount, depleted);
+
+        amount
+    }
+
+    pub fn transfer_envelope(&mut self, envelope_id: i64, from: i64, to: i64, _data: i64) {
+        if self.is_paused_flag()
+            || from == 0
+            || to == 0
+            || !self.is_direct_user_invocation()
+            || self.is_contract_account(to)
+            || !self.exists(envelope_id)
+            || self.is_frozen(envelope_id)
+            || self.env(P_TYPE, envelope_id) != ENVELOPE_TYPE_SPREADING
+            || self.is_recipient_locked(envelope_id)
+            || self.get_i64(k2(P_TOKEN_OWNER, envelope_id)) != from
+        {
+            return;
+        }
+
+        let _ = self.transfer_token(envelope_id, from, to);
+    }
+
+    // Releases a targeted envelope from escrow by minting its NFT straight to `recipient`.
+    pub fn set_envelope_recipient(&mut self, envelope_id: i64, creator: i64, recipient: i64) -> bool {
+        if self.is_paused_flag()
+            || recipient == 0
+            || !self.has_witness(creator)
+            || !self.is_direct_user_invocation()
+            || self.is_contract_account(recipient)
+            || !self.exists(envelope_id)
+            || self.is_frozen(envelope_id)
+            || self.env(P_FLAGS, envelope_id) & ENVELOPE_FLAG_TARGETED == 0
+            || self.env(P_CREATOR, envelope_id) != creator
+            || self.env(P_RECIPIENT, envelope_id) != 0
+            || self.env(P_ACTIVE, envelope_id) == 0
+            || self.now_ms() > self.env(P_EXPIRY, envelope_id)
+        {
+            return false;
+        }
+
+        self.set_env(P_RECIPIENT, envelope_id, recipient);
+        self.mint_token(envelope_id, recipient);
+        true
+    }
+
+    pub fn get_envelope_start(&self, envelope_id: i64) -> i64 {
+        self.env(P_START, envelope_id)
+    }
+
+    pub fn get_envelope_recipient(&self, envelope_id: i64) -> i64 {
+        self.env(P_RECIPIENT, envelope_id)
+    }
+
+    // Full refund before anything is paid out; a spreading NFT must still be with the creator
+    // (or in targeted escrow) so cancelling never pulls an envelope out of someone else's hands.
+    pub fn cancel_envelope(&mut self, envelope_id: i64, creator: i64) -> i64 {
+        let t = self.env(P_TYPE, envelope_id);
+        let holder = self.get_i64(k2(P_TOKEN_OWNER, envelope_id));
+        if self.is_withdraw_blocked()
+            || creator == 0
+            || !self.has_witness(creator)
+            || !self.is_direct_user_invocation()
+            || !self.exists(envelope_id)
+            || self.is_frozen(envelope_id)
+            || t == ENVELOPE_TYPE_CLAIM
+            || self.env(P_CREATOR, envelope_id) != creator
+            || self.env(P_ACTIVE, envelope_id) == 0
+            || self.env(P_OPENED, envelope_id) != 0
+            || self.env(P_PENDING_COMMITS, envelope_id) != 0
+            || (t == ENVELOPE_TYPE_SPREADING && holder != 0 && holder != creator)
+        {
+            return 0;
+        }
+
+        let refund = self.env(P_REMAINING, envelope_id);
+        if refund <= 0 {
+            return 0;
+        }
+
+        if t == ENVELOPE_TYPE_SPREADING {
+            self.burn_token(envelope_id);
+        }
+        self.set_env(P_REMAINING, envelope_id, 0);
+        self.set_env(P_ACTIVE, envelope_id, 0);
+        self.emit(EV_ENVELOPE_REFUNDED, envelope_id, creator, refund);
+        refund
+    }
+
+    // Visits at most `max_count` of the creator's envelopes, starting after the prefix already
+    // settled by earlier calls. The return value totals GAS refunds only, as a single GAS payout;
+    // token envelopes are refunded too and reported by their own `EnvelopeRefunded` events.
+    pub fn reclaim_all_expired(&mut self, creator: i64, max_count: i64) -> i64 {
+        if self.is_withdraw_blocked()
+            || !self.has_witness(creator)
+            || !self.is_direct_user_invocation()
+            || max_count <= 0
+        {
+            return 0;
+        }
+
+        let count = self.get_i64(k2(I_CREATOR_COUNT, creator));
+        let mut cursor = self.get_i64(k2(I_CREATOR_RECLAIM_CURSOR, creator));
+        let end = cursor.saturating_add(max_count).min(count);
+        let mut settled = true;
+        let mut total = 0i64;
+        let mut i = cursor;
+        while i < end {
+            let id = self.get_i64(k3(I_CREATOR_ENVELOPE, creator, i));
+            let refund = if self.is_pool_type(id) {
+                self.reclaim_pool(id, creator)
+            } else {
+                self.reclaim_envelope(id, creator)
+            };
+            if self.env(P_PAY_TOKEN, id) == 0 {
+                total = total.saturating_add(refund);
+            }
+
+            // Stop skipping at the first envelope that may still owe the creator: one with frozen
+            // funds keeps the cursor until it is reclaimed.
+            settled = settled && self.is_reclaim_settled(id);
+            if settled {
+                cursor = i + 1;
+            }
+            i += 1;
+        }
+
+        self.put_i64(k2(I_CREATOR_RECLAIM_CURSOR, creator), cursor);
+        total
+    }
+
+    pub fn reclaim_envelope(&mut self, envelope_id: i64, creator: i64) -> i64 {
+        if self.is_withdraw_blocked()
+            || creator == 0
+            || !self.is_direct_user_invocation()
+            || !self.exists(envelope_id)
+            || self.is_frozen(envelope_id)
+            || self.env(P_TYPE, envelope_id) != ENVELOPE_TYPE_SPREADING
+            || self.env(P_CREATOR, envelope_id) != creator
+            || self.env(P_ACTIVE, envelope_id) == 0
+            || self.now_ms() <= self.env(P_EXPIRY, envelope_id)
+        {
+            return 0;
+        }
+
+        let refund = self.env(P_REMAINING, envelope_id);
+        if refund <= 0 {
+            return 0;
+        }
+
+        self.set_env(P_REMAINING, envelope_id, 0);
+        self.set_env(P_ACTIVE, envelope_id, 0);
+        self.emit(EV_ENVELOPE_REFUNDED, envelope_id, creator, refund);
+        refund
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use red_envelope_core::MemoryRuntime;
+
+    const OWNER: i64 = 1;
+    const ALICE: i64 = 2;
+    const BOB: i64 = 3;
+    const SOME_CONTRACT: i64 = 4;
+    const EXPIRY_MS: i64 = 60_000;
+
+    fn deployed() -> RedEnvelope<MemoryRuntime> {
+        let mut c = RedEnvelope::new(MemoryRuntime::new());
+        c.contract_deploy(OWNER, false);
+        c.runtime_mut().witnesses.insert(OWNER);
+        c
+    }
+
+    fn adapter_payload(envelope_type: i64, packets: i64) -> i64 {
+        ON_NEP17_ADAPTER_BASE
+            + envelope_type * ON_NEP17_ADAPTER_TYPE_MULTIPLIER
+            + packets
+            + EXPIRY_MS * ON_NEP17_ADAPTER_EXPIRY_MULTIPLIER
+    }
+
+    #[test]
+    fn spreading_envelope_opens_per_holder_and_reclaims_after_expiry() {
+        let mut c = deployed();
+        let total = 10 * MIN_AMOUNT;
+        c.on_nep17_payment(ALICE, total, adapter_payload(ENVELOPE_TYPE_SPREADING, 3));
+        assert_eq!(c.owner_of(1), ALICE);
+
+        let first = c.open_envelope(1, ALICE);
+        assert!(first > 0);
+        assert_eq!(c.open_envelope(1, ALICE), 0);
+
+        c.transfer_envelope(1, ALICE, BOB, 0);
+        let second = c.open_envelope(1, BOB);
+        assert!(second > 0);
+        assert_eq!(c.env(P_REMAINING, 1), total - first - second);
+
+        assert_eq!(c.reclaim_envelope(1, ALICE), 0);
+        c.runtime_mut().now_ms = EXPIRY_MS + 1;
+        assert_eq!(c.reclaim_envelope(1, ALICE), total - first - second);
+        assert!(c.runtime().notifications.iter().any(|n| n.event == EV_ENVELOPE_REFUNDED
+            && (n.a, n.b, n.c) == (1, ALICE, total - first - second)));
+    }
+
+    #[test]
+    fn pool_claims_are_single_use_and_refuse_contracts() {
+        let mut c = deployed();
+        let total = 10 * MIN_AMOUNT;
+        c.on_nep17_payment(ALICE, total, adapter_payload(ENVELOPE_TYPE_POOL, 2));
+
+        let claim_id = c.claim_from_pool(1, BOB);
+        assert_eq!(claim_id, 2);
+        assert_eq!(c.claim_from_pool(1, BOB), 0);
+        assert_eq!(c.check_open_eligibility(1, BOB), E_ALREADY_CLAIMED);
+
+        c.runtime_mut().contracts.insert(SOME_CONTRACT);
+        assert_eq!(c.claim_from_pool(1, SOME_CONTRACT), 0);
+        assert_eq!(c.check_open_eligibility(1, SOME_CONTRACT), E_CONTRACT_ACCOUNT);
+
+        // The unopened claim NFT flows back to the creator together with the pool remainder.
+        c.runtime_mut().now_ms = EXPIRY_MS + 1;
+        assert_eq!(c.reclaim_pool(1, ALICE), total);
+        assert_eq!(c.open_claim(claim_id, BOB), 0);
+    }
+
+    #[test]
+    fn commit_reveal_keeps_the_slot_reserved_until_reveal_or_expiry() {
+        let mut c = deployed();
+        let total = 10 * MIN_AMOUNT;
+        let payload = adapter_payload(ENVELOPE_TYPE_POOL, 2)
+            + ENVELOPE_FLAG_COMMIT_REVEAL * ON_NEP17_ADAPTER_FLAGS_MULTIPLIER;
+        c.on_nep17_payment(ALICE, total, payload);
+        c.runtime_mut().witnesses.extend([ALICE, BOB]);
+
+        assert!(c.commit_pool_claim(1, BOB));
+        assert!(!c.commit_pool_claim(1, BOB));
+        c.runtime_mut().now_ms = 1;
+        c.runtime_mut().block_index = 1;
+        assert_eq!(c.reveal_pool_claim(1, BOB), 0);
+        c.runtime_mut().block_index = 2;
+        let claim_id = c.reveal_pool_claim(1, BOB);
+        assert!(claim_id > 0);
+        assert_eq!(c.get_pool_commit_state(1, BOB), COMMIT_REVEALED);
+        assert_eq!(c.env(P_PENDING_COMMITS, 1), 0);
+
+        // A reveal whose mint fails must not release the reservation, however often it is retried.
+        assert!(c.commit_pool_claim(1, ALICE));
+        let remaining = c.env(P_REMAINING, 1);
+        c.set_env(P_REMAINING, 1, 0);
+        c.runtime_mut().now_ms = 2;
+        c.runtime_mut().block_index = 4;
+        assert_eq!(c.reveal_pool_claim(1, ALICE), 0);
+        assert_eq!(c.reveal_pool_claim(1, ALICE), 0);
+        assert_eq!(c.get_pool_commit_state(1, ALICE), COMMIT_PENDING);
+        assert_eq!(c.env(P_PENDING_COMMITS, 1), 1);
+        c.set_env(P_REMAINING, 1, remaining);
+
+        assert!(!c.expire_pool_commit(1, ALICE));
+        c.runtime_mut().now_ms = EXPIRY_MS + 1;
+        assert!(c.expire_pool_commit(1, ALICE));
+        assert!(!c.expire_pool_commit(1, ALICE));
+        assert_eq!(c.get_pool_commit_state(1, ALICE), COMMIT_EXPIRED);
+        assert_eq!(c.env(P_PENDING_COMMITS, 1), 0);
+    }
+
+    #[test]
+    fn reveal_roll_is_fixed_by_the_block_after_the_commit() {
+        let payload = adapter_payload(ENVELOPE_TYPE_POOL, 5)
+            + ENVELOPE_FLAG_COMMIT_REVEAL * ON_NEP17_ADAPTER_FLAGS_MULTIPLIER;
+        let reveal = |entropy: i64| {
+            let mut c = deployed();
+            c.on_nep17_payment(ALICE, 10 * MIN_AMOUNT, payload);
+            c.runtime_mut().witnesses.insert(BOB);
+            assert!(c.commit_pool_claim(1, BOB));
+            c.runtime_mut().block_index = 2;
+            c.runtime_mut().block_nonces.insert(2, 0x5eed);
+            c.runtime_mut().entropy = entropy;
+            let claim_id = c.reveal_pool_claim(1, BOB);
+            c.get_claim_state(claim_id)
+        };
+
+        // Transaction randomness differs between reveal attempts; the committed block's does not.
+        assert!(reveal(1) > 0);
+        assert_eq!(reveal(1), reveal(987_654_321));
+    }
+
+    #[test]
+    fn equal_split_pays_fixed_shares_with_remainder_last() {
+        let mut c = deployed();
+        let total = 10 * MIN_AMOUNT + 1;
+        c.on_nep17_payment(ALICE, total, adapter_payload(ENVELOPE_TYPE_EQUAL, 2));
+        assert_eq!(c.calculate_packet_amount(1, 0), total / 2);
+
+        let first = c.claim_from_pool(1, BOB);
+        let second = c.claim_from_pool(1, ALICE);
+        assert_eq!(c.get_claim_state(first), total / 2);
+        assert_eq!(c.get_claim_state(second), total - total / 2);
+        assert_eq!(c.env(P_REMAINING, 1), 0);
+        assert_eq!(c.check_open_eligibility(1, BOB), E_NOT_ACTIVE);
+    }
+
+    #[test]
+    fn secret_pool_pays_only_the_committed_code() {
+        const CODE: i64 = 8_888;
+        let mut c = deployed();
+        let secret_pool = adapter_payload(ENVELOPE_TYPE_POOL, 2)
+            + ENVELOPE_FLAG_SECRET * ON_NEP17_ADAPTER_FLAGS_MULTIPLIER;
+        c.on_nep17_payment(ALICE, 10 * MIN_AMOUNT, secret_pool);
+        c.runtime_mut().witnesses.extend([ALICE, BOB]);
+
+        let commitment = red_envelope_core::secret_commitment(c.runtime(), 1, CODE);
+        assert_eq!(c.check_code_eligibility(1, BOB, CODE), E_WRONG_CODE);
+        assert!(c.set_pool_secret(1, ALICE, commitment));
+        assert!(!c.set_pool_secret(1, ALICE, commitment + 1));
+
+        assert_eq!(c.claim_from_pool(1, BOB), 0);
+        assert_eq!(c.check_code_eligibility(1, BOB, CODE + 1), E_WRONG_CODE);
+        assert_eq!(c.claim_from_pool_with_code(1, BOB, CODE + 1), 0);
+        assert_eq!(c.claim_from_pool_with_code(1, BOB, CODE), 2);
+        assert_eq!(c.check_code_eligibility(1, BOB, CODE), E_ALREADY_CLAIMED);
+    }
+
+    #[test]
+    fn allowlist_pool_requires_a_folded_proof() {
+        let mut c = deployed();
+        let members = [ALICE, BOB, 5];
+        let allowlist_pool = adapter_payload(ENVELOPE_TYPE_EQUAL, 3)
+            + ENVELOPE_FLAG_ALLOWLIST * ON_NEP17_ADAPTER_FLAGS_MULTIPLIER;
+        c.on_nep17_payment(ALICE, 10 * MIN_AMOUNT, allowlist_pool);
+        c.runtime_mut().witnesses.extend([ALICE, BOB, 6]);
+
+        let root = red_envelope_core::merkle_root(c.runtime(), &members);
+        assert!(c.set_pool_allowlist(1, ALICE, root));
+
+        assert_eq!(c.check_open_eligibility(1, BOB), E_NOT_ALLOWLISTED);
+        assert_eq!(c.claim_from_pool(1, BOB), 0);
+        for sibling in red_envelope_core::merkle_proof(c.runtime(), &members, BOB).unwrap() {
+            c.prove_allowlist(1, BOB, sibling);
+        }
+        assert_eq!(c.check_open_eligibility(1, BOB), ELIGIBILITY_OK);
+        assert_eq!(c.claim_from_pool(1, BOB), 2);
+
+        // A member's proof does not carry over to an outsider.
+        for sibling in red_envelope_core::merkle_proof(c.runtime(), &members, BOB).unwrap() {
+            c.prove_allowlist(1, 6, sibling);
+        }
+        assert_eq!(c.claim_from_pool(1, 6), 0);
+        assert_eq!(c.check_open_eligibility(1, 6), E_NOT_ALLOWLISTED);
+    }
+
+    #[test]
+    fn targeted_envelope_escrows_until_recipient_is_named() {
+        let mut c = deployed();
+        let total = 10 * MIN_AMOUNT;
+        let targeted = adapter_payload(ENVELOPE_TYPE_SPREADING, 1)
+            + (ENVELOPE_FLAG_TARGETED | ENVELOPE_FLAG_RECIPIENT_ONLY)
+                * ON_NEP17_ADAPTER_FLAGS_MULTIPLIER;
+        c.on_nep17_payment(ALICE, total, targeted);
+        c.runtime_mut().witnesses.insert(ALICE);
+
+        assert_eq!(c.owner_of(1), 0);
+        assert_eq!(c.open_envelope(1, ALICE), 0);
+        assert_eq!(c.check_open_eligibility(1, BOB), E_NOT_STARTED);
+        assert!(c.set_envelope_recipient(1, ALICE, BOB));
+        assert!(!c.set_envelope_recipient(1, ALICE, ALICE));
+        assert_eq!(c.owner_of(1), BOB);
+        assert_eq!(c.check_open_eligibility(1, BOB), ELIGIBILITY_OK);
+
+        c.transfer_envelope(1, BOB, ALICE, 0);
+        assert_eq!(c.owner_of(1), BOB);
+        assert_eq!(c.open_envelope(1, BOB), total);
+    }
+
+    #[test]
+    fn whitelisted_token_envelopes_use_token_minimums() {
+        const FUSDT: i64 = 77;
+        let mut c = deployed();
+        assert!(c.set_token_config(FUSDT, 1_000, 100));
+        c.runtime_mut().calling_contract = FUSDT;
+
+        // Far below the GAS minimum, but above the token's own floor.
+        c.on_nep17_payment(ALICE, 2_000, adapter_payload(ENVELOPE_TYPE_POOL, 2));
+        assert_eq!(c.get_envelope_token(1), FUSDT);
+        assert_eq!(c.get_total_distributed(), 0);
+
+        let claim_id = c.claim_from_pool(1, BOB);
+        assert_eq!(c.get_envelope_token(claim_id), FUSDT);
+        assert!(c.get_claim_state(claim_id) >= 100);
+
+        assert!(!c.set_token_config(GAS_TOKEN, 1_000, 100));
+        assert!(c.set_token_config(FUSDT, 0, 0));
+    }
+
+    #[test]
+    #[should_panic(expected = "contract aborted")]
+    fn delisted_token_deposits_abort_instead_of_becoming_gas() {
+        const FUSDT: i64 = 77;
+        let mut c = deployed();
+        assert!(c.set_token_config(FUSDT, 1_000, 100));
+        assert!(c.set_token_config(FUSDT, 0, 0));
+        c.runtime_mut().calling_contract = FUSDT;
+        c.on_nep17_payment(ALICE, 10 * MIN_AMOUNT, adapter_payload(ENVELOPE_TYPE_POOL, 2));
+    }
+
+    #[test]
+    fn scheduled_envelope_waits_for_start_and_expires_from_it() {
+        const START_MINUTES: i64 = 30;
+        let start = START_MINUTES * ON_NEP17_ADAPTER_MINUTE_MS;
+        let mut c = deployed();
+        let scheduled = adapter_payload(ENVELOPE_TYPE_POOL, 2)
+            + START_MINUTES * ON_NEP17_ADAPTER_START_MULTIPLIER;
+        c.on_nep17_payment(ALICE, 10 * MIN_AMOUNT, scheduled);
+        assert_eq!(c.get_envelope_start(1), start);
+
+        assert_eq!(c.check_open_eligibility(1, BOB), E_NOT_STARTED);
+        assert_eq!(c.claim_from_pool(1, BOB), 0);
+
+        c.runtime_mut().now_ms = start + EXPIRY_MS;
+        assert_eq!(c.claim_from_pool(1, BOB), 2);
+        c.runtime_mut().now_ms = start + EXPIRY_MS + 1;
+        assert_eq!(c.check_open_eligibility(1, ALICE), E_EXPIRED);
+    }
+
+    #[cfg(feature = "testnet")]
+    #[test]
+    fn eligibility_follows_the_testnet_time_override() {
+        let mut c = deployed();
+        c.on_nep17_payment(ALICE, 10 * MIN_AMOUNT, adapter_payload(ENVELOPE_TYPE_POOL, 2));
+        assert_eq!(c.check_open_eligibility(1, BOB), ELIGIBILITY_OK);
+
+        assert!(c.set_time_override(EXPIRY_MS + 1));
+        assert_eq!(c.check_open_eligibility(1, BOB), E_EXPIRED);
+        assert_eq!(c.claim_from_pool(1, BOB), 0);
+
+        assert!(c.clear_time_override());
+        assert_eq!(c.claim_from_pool(1, BOB), 2);
+    }
+
+    #[test]
+    fn cancel_refunds_untouched_envelopes_and_burns_the_nft() {
+        let mut c = deployed();
+        let total = 10 * MIN_AMOUNT;
+        c.on_nep17_payment(ALICE, total, adapter_payload(ENVELOPE_TYPE_SPREADING, 3));
+        c.on_nep17_payment(ALICE, total, adapter_payload(ENVELOPE_TYPE_SPREADING, 3));
+        assert_eq!(c.cancel_envelope(1, ALICE), 0);
+
+        c.runtime_mut().witnesses.insert(ALICE);
+        assert_eq!(c.cancel_envelope(1, ALICE), total);
+        assert_eq!(c.owner_of(1), 0);
+        assert_eq!(c.balance_of(ALICE), 1);
+        assert_eq!(c.cancel_envelope(1, ALICE), 0);
+
+        c.transfer_envelope(2, ALICE, BOB, 0);
+        assert_eq!(c.cancel_envelope(2, ALICE), 0);
+        assert!(c.runtime().notifications.iter().any(|n| n.event == EV_ENVELOPE_REFUNDED
+            && (n.a, n.b, n.c) == (1, ALICE, total)));
+    }
+
+    fn top_up_payload(envelope_id: i64, add_packets: i64, extend_minutes: i64) -> i64 {
+        -(ON_NEP17_TOPUP_BASE
+            + envelope_id
+            + add_packets * ON_NEP17_TOPUP_PACKETS_MULTIPLIER
+            + extend_minutes * ON_NEP17_TOPUP_EXTEND_MULTIPLIER)
+    }
+
+    #[test]
+    fn creator_top_up_refills_a_depleted_pool() {
+        let mut c = deployed();
+        let total = 10 * MIN_AMOUNT;
+        c.on_nep17_payment(ALICE, total, adapter_payload(ENVELOPE_TYPE_POOL, 1));
+        let first = c.claim_from_pool(1, BOB);
+        assert_eq!(c.get_claim_state(first), total);
+        assert_eq!(c.check_open_eligibility(1, ALICE), E_NOT_ACTIVE);
+
+        c.on_nep17_payment(ALICE, total, top_up_payload(1, 1, 10));
+        assert_eq!(c.env(P_REMAINING, 1), total);
+        assert_eq!(c.get_total_envelopes(), 1);
+        assert_eq!(c.get_total_distributed(), 2 * total);
+
+        c.runtime_mut().now_ms = EXPIRY_MS + 1;
+        assert_eq!(c.claim_from_pool(1, ALICE), first + 1);
+    }
+
+    #[test]
+    #[should_panic(expected = "contract aborted")]
+    fn refused_top_up_aborts_instead_of_keeping_the_deposit() {
+        let mut c = deployed();
+        c.on_nep17_payment(ALICE, 10 * MIN_AMOUNT, adapter_payload(ENVELOPE_TYPE_POOL, 2));
+        c.on_nep17_payment(BOB, MIN_AMOUNT, top_up_payload(1, 1, 10));
+    }
+
+    #[test]
+    #[should_panic(expected = "contract aborted")]
+    fn paused_top_up_aborts_instead_of_keeping_the_deposit() {
+        let mut c = deployed();
+        c.on_nep17_payment(ALICE, 10 * MIN_AMOUNT, adapter_payload(ENVELOPE_TYPE_POOL, 2));
+        c.pause();
+        c.on_nep17_payment(ALICE, MIN_AMOUNT, top_up_payload(1, 1, 10));
+    }
+
+    #[test]
+    fn scheduled_top_up_measures_the_expiry_cap_from_the_start() {
+        const START_MINUTES: i64 = 3 * 24 * 60;
+        let mut c = deployed();
+        let scheduled = adapter_payload(ENVELOPE_TYPE_POOL, 2)
+            + START_MINUTES * ON_NEP17_ADAPTER_START_MULTIPLIER;
+        c.on_nep17_payment(ALICE, 10 * MIN_AMOUNT, scheduled);
+
+        // Nine days out from now, but only six past the scheduled start.
+        c.on_nep17_payment(ALICE, MIN_AMOUNT, top_up_payload(1, 0, 6 * 24 * 60));
+        assert_eq!(c.env(P_REMAINING, 1), 11 * MIN_AMOUNT);
+    }
+
+    fn batch_payload(count: i64, envelope_type: i64, packets: i64) -> i64 {
+        -(ON_NEP17_BATCH_BASE
+            + packets
+            + envelope_type * ON_NEP17_BATCH_TYPE_MULTIPLIER
+            + count * ON_NEP17_BATCH_COUNT_MULTIPLIER
+            + EXPIRY_MS / ON_NEP17_ADAPTER_MINUTE_MS * ON_NEP17_BATCH_EXPIRY_MULTIPLIER)
+    }
+
+    #[test]
+    fn batch_deposit_creates_identical_envelopes() {
+        let mut c = deployed();
+        let each = 2 * MIN_AMOUNT;
+        c.on_nep17_payment(ALICE, 3 * each, batch_payload(3, ENVELOPE_TYPE_POOL, 2));
+        assert_eq!(c.get_total_envelopes(), 3);
+        assert_eq!(c.get_total_distributed(), 3 * each);
+        for id in 1..=3 {
+            assert_eq!(c.env(P_REMAINING, id), each);
+            assert_eq!(c.check_open_eligibility(id, BOB), ELIGIBILITY_OK);
+        }
+        assert_eq!(
+            c.runtime().notifications.last().map(|n| (n.event, n.a, n.b, n.c)),
+            Some((EV_ENVELOPE_BATCH_CREATED, 1, 3, each))
+        );
+
+        c.runtime_mut().now_ms = EXPIRY_MS + 1;
+        assert_eq!(c.check_open_eligibility(3, BOB), E_EXPIRED);
+    }
+
+    #[test]
+    #[should_panic(expected = "contract aborted")]
+    fn uneven_batch_deposit_aborts_instead_of_keeping_the_deposit() {
+        let mut c = deployed();
+        let each = 2 * MIN_AMOUNT;
+        c.on_nep17_payment(ALICE, 3 * each + 1, batch_payload(3, ENVELOPE_TYPE_POOL, 2));
+    }
+
+    #[test]
+    fn batches_report_per_item_and_strict_mode_is_all_or_nothing() {
+        const FUSDT: i64 = 77;
+        let mut c = deployed();
+        let total = 10 * MIN_AMOUNT;
+        c.on_nep17_payment(ALICE, total, adapter_payload(ENVELOPE_TYPE_EQUAL, 2));
+        c.on_nep17_payment(ALICE, total, adapter_payload(ENVELOPE_TYPE_EQUAL, 2));
+        assert!(c.set_token_config(FUSDT, 1_000, 100));
+        c.runtime_mut().calling_contract = FUSDT;
+        c.on_nep17_payment(ALICE, 2_000, adapter_payload(ENVELOPE_TYPE_POOL, 2));
+        c.runtime_mut().witnesses.insert(BOB);
+
+        assert_eq!(c.claim_from_pools(BOB, true, &[1, 2, 1]), 0);
+        assert_eq!(c.claim_from_pools(BOB, true, &[1, 2, 99]), 0);
+        assert!(!c.has_claimed_from_pool(1, BOB));
+        // Returns how many claims were minted; their value is paid once, by `openClaims`.
+        assert_eq!(c.claim_from_pools(BOB, false, &[1, 0, 2, 1, 3]), 3);
+        assert!(c.has_claimed_from_pool(3, BOB));
+
+        let claims = [
+            c.get_pool_claim_id_by_index(1, 1),
+            c.get_pool_claim_id_by_index(2, 1),
+            c.get_pool_claim_id_by_index(3, 1),
+        ];
+        let token_amount = c.get_claim_state(claims[2]);
+        assert_eq!(c.open_claims(BOB, true, &[claims[0], claims[1], 99]), 0);
+        // The GAS total excludes the token claim, whose amount is only in its item result.
+        assert_eq!(c.open_claims(BOB, false, &[claims[0], claims[1], claims[2], 99]), total);
+        let results: Vec<_> = c
+            .runtime()
+            .notifications
+            .iter()
+            .filter(|n| n.event == EV_BATCH_ITEM_RESULT)
+            .map(|n| (n.a, n.b, n.c))
+            .collect();
+        assert_eq!(results.len(), 8);
+        assert_eq!(results[2], (3, 1, 0));
+        assert_eq!(results[3], (4, 3, claims[2]));
+        assert_eq!(results[6], (2, claims[2], token_amount));
+        assert_eq!(results[7], (3, 99, 0));
+    }
+
+    #[test]
+    fn reclaim_all_expired_walks_the_creator_index() {
+        let mut c = deployed();
+        let total = 10 * MIN_AMOUNT;
+        c.on_nep17_payment(ALICE, total, adapter_payload(ENVELOPE_TYPE_SPREADING, 2));
+        c.on_nep17_payment(ALICE, total, adapter_payload(ENVELOPE_TYPE_POOL, 2));
+        c.on_nep17_payment(BOB, total, adapter_payload(ENVELOPE_TYPE_SPREADING, 2));
+        c.on_nep17_payment(ALICE, total, adapter_payload(ENVELOPE_TYPE_SPREADING, 2));
+        let opened = c.open_envelope(1, ALICE);
+        let claim_id = c.claim_from_pool(2, BOB);
+        c.runtime_mut().witnesses.insert(ALICE);
+
+        assert_eq!(c.reclaim_all_expired(ALICE, 10), 0);
+        c.runtime_mut().now_ms = EXPIRY_MS + 1;
+        assert_eq!(c.reclaim_all_expired(ALICE, 2), 2 * total - opened);
+        assert_eq!(c.open_claim(claim_id, BOB), 0);
+        assert_eq!(c.reclaim_all_expired(ALICE, 2), total);
+        assert_eq!(c.reclaim_all_expired(ALICE, 10), 0);
+        assert_eq!(c.reclaim_envelope(3, BOB), total);
+    }
+
+    #[test]
+    fn reclaim_all_expired_refunds_token_envelopes_outside_the_gas_total() {
+        const FUSDT: i64 = 77;
+        let mut c = deployed();
+        let total = 10 * MIN_AMOUNT;
+        assert!(c.set_token_config(FUSDT, 1_000, 100));
+        c.runtime_mut().calling_contract = FUSDT;
+        c.on_nep17_payment(ALICE, 2_000, adapter_payload(ENVELOPE_TYPE_SPREADING, 2));
+        c.runtime_mut().calling_contract = GAS_TOKEN;
+        c.on_nep17_payment(ALICE, total, adapter_payload(ENVELOPE_TYPE_SPREADING, 2));
+        c.runtime_mut().witnesses.insert(ALICE);
+
+        c.runtime_mut().now_ms = EXPIRY_MS + 1;
+        assert_eq!(c.reclaim_all_expired(ALICE, 10), total);
+        assert!(c.runtime().notifications.iter().any(|n| n.event == EV_ENVELOPE_REFUNDED
+            && (n.a, n.b, n.c) == (1, ALICE, 2_000)));
+        assert_eq!(c.reclaim_envelope(1, ALICE), 0);
+        assert_eq!(c.reclaim_all_expired(ALICE, 10), 0);
+    }
+
+    #[test]
+    fn reclaim_all_expired_waits_on_frozen_claims() {
+        let mut c = deployed();
+        let total = 10 * MIN_AMOUNT;
+        c.on_nep17_payment(ALICE, total, adapter_payload(ENVELOPE_TYPE_POOL, 2));
+        let claim_id = c.claim_from_pool(1, BOB);
+        let claimed = c.get_claim_state(claim_id);
+        c.on_nep17_payment(ALICE, total, adapter_payload(ENVELOPE_TYPE_SPREADING, 2));
+        assert!(c.freeze(claim_id));
+        c.runtime_mut().witnesses.insert(ALICE);
+
+        c.runtime_mut().now_ms = EXPIRY_MS + 1;
+        assert_eq!(c.reclaim_all_expired(ALICE, 10), 2 * total - claimed);
+
+        // The frozen claim kept the cursor on its pool, so the next pass still pays it out.
+        assert!(c.unfreeze(claim_id));
+        assert_eq!(
+            c.runtime()
+                .notifications
+                .last()
+                .map(|n| (n.event, n.a, n.b, n.c)),
+            Some((EV_ENVELOPE_UNFROZEN, claim_id, ENVELOPE_TYPE_CLAIM, EXPIRY_MS + 1))
+        );
+        assert_eq!(c.reclaim_all_expired(ALICE, 1), claimed);
+        assert_eq!(c.reclaim_all_expired(ALICE, 10), 0);
+        assert!(c.runtime().notifications.iter().any(|n| n.event == EV_ENVELOPE_REFUNDED
+            && (n.a, n.b, n.c) == (1, ALICE, claimed)));
+    }
+
+    #[test]
+    fn creator_and_holder_indexes_follow_creation_claims_and_transfers() {
+        let mut c = deployed();
+        let total = 10 * MIN_AMOUNT;
+        c.on_nep17_payment(ALICE, total, adapter_payload(ENVELOPE_TYPE_POOL, 3));
+        c.on_nep17_payment(ALICE, total, adapter_payload(ENVELOPE_TYPE_POOL, 3));
+        let first = c.claim_from_pool(1, BOB);
+        let second = c.claim_from_pool(2, BOB);
+
+        assert_eq!(c.get_creator_envelope_count(ALICE), 2);
+        assert_eq!(c.get_envelope_by_creator_index(ALICE, 1), 2);
+        assert_eq!(c.get_envelope_by_creator_index(ALICE, 2), 0);
+        assert_eq!(c.get_holder_claim_count(BOB), 2);
+
+        c.transfer_claim(first, BOB, ALICE);
+        assert_eq!(c.get_holder_claim_count(BOB), 1);
+        assert_eq!(c.get_claim_by_holder_index(BOB, 0), second);
+        assert_eq!(c.get_holder_claim_count(ALICE), 1);
+        assert_eq!(c.get_claim_by_holder_index(ALICE, 0), first);
+
+        let page = c.get_envelopes_by_creator(ALICE, 0, 10);
+        assert_eq!(c.runtime().array(page), [1, 2]);
+        let page = c.get_envelopes_by_creator(ALICE, 1, 10);
+        assert_eq!(c.runtime().array(page), [2]);
+        let page = c.get_claims_by_holder(BOB, 0, 1);
+        assert_eq!(c.runtime().array(page), [second]);
+        let page = c.get_claims_by_holder(BOB, 1, 1);
+        assert!(c.runtime().array(page).is_empty());
+    }
+
+    #[test]
+    fn nep11_transfer_needs_the_current_owner_witness() {
+        let mut c = deployed();
+        c.on_nep17_payment(ALICE, 10 * MIN_AMOUNT, adapter_payload(ENVELOPE_TYPE_POOL, 2));
+        let claim_id = c.claim_from_pool(1, BOB);
+
+        assert!(!c.transfer(ALICE, claim_id, 0));
+        c.runtime_mut().witnesses.insert(BOB);
+        assert!(c.transfer(ALICE, claim_id, 0));
+        assert_eq!(c.owner_of(claim_id), ALICE);
+    }
+
+    #[test]
+    fn pool_claim_records_keep_the_original_claimer() {
+        let mut c = deployed();
+        let total = 10 * MIN_AMOUNT;
+        c.on_nep17_payment(ALICE, total, adapter_payload(ENVELOPE_TYPE_EQUAL, 2));
+        let first = c.claim_from_pool(1, BOB);
+        let second = c.claim_from_pool(1, ALICE);
+        c.transfer_claim(first, BOB, ALICE);
+        assert!(c.open_claim(second, ALICE) > 0);
+
+        assert_eq!(c.get_pool_claim_count(1), 2);
+        assert_eq!(c.get_pool_claimer(1, 1), BOB);
+        assert!(!c.is_claim_opened(first));
+        assert!(c.is_claim_opened(second));
+
+        let mut records = [red_envelope_core::PoolClaimRecord::default(); 4];
+        let count = c.get_pool_claim_count(1);
+        let n = red_envelope_core::page(count, 0, &mut records, |i| {
+            red_envelope_core::pool_claim_record(c.runtime(), 1, i + 1)
+        });
+        assert_eq!(n, 2);
+        assert_eq!(
+            records[1],
+            red_envelope_core::PoolClaimRecord {
+                claim_index: 2,
+                claim_id: second,
+                claimer: ALICE,
+                amount: total / 2,
+                opened: true,
+            }
+        );
+
+        let rows = c.get_pool_claims(1, 1, 10);
+        assert_eq!(c.runtime().array(rows).len(), 1);
+        let row = c.runtime().array(rows)[0];
+        assert_eq!(c.runtime().array(row), [2, second, ALICE, total / 2, 1]);
+        let rows = c.get_pool_claims(1, 2, 10);
+        assert!(c.runtime().array(rows).is_empty());
+    }
+
+    #[test]
+    fn luckiest_claimer_tracks_the_best_packet_until_depleted() {
+        let mut c = deployed();
+        let total = 10 * MIN_AMOUNT;
+        c.on_nep17_payment(ALICE, total, adapter_payload(ENVELOPE_TYPE_POOL, 2));
+        let first = c.claim_from_pool(1, BOB);
+        let second = c.claim_from_pool(1, ALICE);
+        let (bob, alice) = (c.get_claim_state(first), c.get_claim_state(second));
+        let (holder, best) = if alice > bob { (ALICE, alice) } else { (BOB, bob) };
+
+        assert_eq!(c.get_luckiest_claimer(1), holder);
+        assert_eq!(c.get_luckiest_amount(1), best);
+        let state = c.get_envelope_state(1);
+        let state = c.runtime().array(state);
+        assert_eq!(state[..6], [1, ALICE, total, 2, 2, 0]);
+        assert_eq!(state[11..], [holder, best]);
+        assert_eq!(
+            c.runtime().notifications.last().map(|n| (n.event, n.a, n.b, n.c)),
+            Some((EV_LUCKIEST_FINALIZED, 1, holder, best))
+        );
+        let updates = c
+            .runtime()
+            .notifications
+            .iter()
+            .filter(|n| n.event == EV_LUCKIEST_UPDATED)
+            .count();
+        assert_eq!(updates, if alice > bob { 2 } else { 1 });
+    }
+
+    #[test]
+    fn upgrades_wait_for_the_timelock_and_can_be_cancelled() {
+        const NEF: i64 = 0x4e4546;
+        const MANIFEST: i64 = 0x6d616e;
+        let mut c = deployed();
+
+        let op_id = c.schedule_upgrade(NEF, MANIFEST);
+        assert_eq!(op_id, 1);
+        assert_eq!(c.get_admin_operation_eta(op_id), ADMIN_TIMELOCK_DELAY_MS);
+        assert_eq!(c.get_admin_operation_kind(op_id), ADMIN_OP_UPGRADE);
+        assert_eq!(
+            c.get_admin_operation_nef_hash(op_id),
+            red_envelope_core::Runtime::sha256_raw(c.runtime(), NEF)
+        );
+        assert_eq!(
+            c.get_admin_operation_manifest_hash(op_id),
+            red_envelope_core::Runtime::sha256_raw(c.runtime(), MANIFEST)
+        );
+        assert_eq!(c.get_admin_operation_constant(op_id, CONST_MIN_AMOUNT), 0);
+        assert!(!c.execute_admin_operation(op_id));
+        assert_eq!(c.get_admin_operation(op_id), ADMIN_OP_QUEUED);
+
+        c.runtime_mut().now_ms = ADMIN_TIMELOCK_DELAY_MS;
+        assert!(c.execute_admin_operation(op_id));
+        assert!(!c.execute_admin_operation(op_id));
+        assert_eq!(c.get_admin_operation(op_id), ADMIN_OP_EXECUTED);
+        assert_eq!(c.runtime().updates, [(NEF, MANIFEST)]);
+        assert!(c.runtime().notifications.iter().any(|n| n.event == EV_ADMIN_OP_EXECUTED
+            && (n.a, n.b, n.c) == (op_id, ADMIN_OP_UPGRADE, ADMIN_TIMELOCK_DELAY_MS)));
+
+        let cancelled = c.schedule_upgrade(NEF, MANIFEST);
+        assert!(c.cancel_admin_operation(cancelled));
+        assert!(!c.cancel_admin_operation(cancelled));
+        c.runtime_mut().now_ms = 2 * ADMIN_TIMELOCK_DELAY_MS;
+        assert!(!c.execute_admin_operation(cancelled));
+        assert_eq!(c.get_admin_operation(cancelled), ADMIN_OP_CANCELLED);
+
+        c.runtime_mut().witnesses.clear();
+        assert_eq!(c.schedule_upgrade(NEF, MANIFEST), 0);
+        assert_eq!(c.runtime().updates.len(), 1);
+
+        // Without the owner's witness nobody can take over the timelock.
+        c.set_owner(BOB);
+        assert_eq!(c.get_owner(), OWNER);
+    }
+
+    #[test]
+    fn retuned_constants_only_reach_new_envelopes() {
+        let raised = MIN_AMOUNT / 2;
+        let mut c = deployed();
+        let op_id = c.set_calculation_constants(
+            MIN_AMOUNT,
+            MAX_PACKETS,
+            raised,
+            DENSE_VOLATILITY_LOW_BPS,
+            DENSE_VOLATILITY_HIGH_BPS,
+            MEDIUM_VOLATILITY_LOW_BPS,
+            MEDIUM_VOLATILITY_HIGH_BPS,
+            SPARSE_VOLATILITY_LOW_BPS,
+            SPARSE_VOLATILITY_HIGH_BPS,
+            MAX_EXPIRY_MS,
+        );
+
+        assert_eq!(c.get_admin_operation_kind(op_id), ADMIN_OP_SET_CONSTANTS);
+        assert_eq!(c.get_admin_operation_constant(op_id, CONST_MIN_PER_PACKET), raised);
+
+        c.runtime_mut().now_ms = ADMIN_TIMELOCK_DELAY_MS - 1;
+        c.on_nep17_payment(ALICE, MIN_AMOUNT, adapter_payload(ENVELOPE_TYPE_POOL, 10));
+        c.runtime_mut().now_ms = ADMIN_TIMELOCK_DELAY_MS;
+        assert!(c.execute_admin_operation(op_id));
+        assert_eq!(c.get_calculation_constant(CONST_MIN_PER_PACKET), raised);
+        let constants = c.get_calculation_constants();
+        assert_eq!(
+            c.runtime().array(constants),
+            [
+                MIN_AMOUNT,
+                MAX_PACKETS,
+                raised,
+                DENSE_VOLATILITY_LOW_BPS,
+                DENSE_VOLATILITY_HIGH_BPS,
+                MEDIUM_VOLATILITY_LOW_BPS,
+                MEDIUM_VOLATILITY_HIGH_BPS,
+                SPARSE_VOLATILITY_LOW_BPS,
+                SPARSE_VOLATILITY_HIGH_BPS,
+                MAX_EXPIRY_MS,
+            ]
+        );
+
+        // Ten packets no longer fit the live floor, but the pool keeps the terms it was created with.
+        let mut claimed = 0;
+        for claimer in 10..20 {
+            assert!(c.claim_from_pool(1, claimer) > 0);
+            claimed += c.get_pool_claimed_amount(1, claimer);
+        }
+        assert_eq!(claimed, MIN_AMOUNT);
+        assert_eq!(c.env(P_REMAINING, 1), 0);
+    }
+
+    #[test]
+    #[should_panic(expected = "contract aborted")]
+    fn pause_without_the_owner_witness_aborts() {
+        let mut c = deployed();
+        c.pause();
+        assert!(c.is_paused());
+        c.resume();
+        assert!(!c.is_paused());
+
+        c.runtime_mut().witnesses.remove(&OWNER);
+        c.pause();
+    }
+
+    #[test]
+    fn withdraw_only_pause_blocks_opens_but_not_reclaims() {
+        let mut c = deployed();
+        let total = 10 * MIN_AMOUNT;
+        c.on_nep17_payment(ALICE, total, adapter_payload(ENVELOPE_TYPE_SPREADING, 3));
+
+        c.runtime_mut().now_ms = 5;
+        assert!(c.set_pause_level(PAUSE_WITHDRAW_ONLY));
+        assert_eq!(
+            c.runtime()
+                .notifications
+                .last()
+                .map(|n| (n.event, n.a, n.b, n.c)),
+            Some((EV_PAUSE_LEVEL_CHANGED, PAUSE_WITHDRAW_ONLY, PAUSE_NONE, 5))
+        );
+        assert_eq!(c.open_envelope(1, ALICE), 0);
+
+        c.runtime_mut().now_ms = EXPIRY_MS + 1;
+        assert_eq!(c.reclaim_envelope(1, ALICE), total);
+        assert_eq!(
+            c.runtime()
+                .notifications
+                .last()
+                .map(|n| (n.event, n.a, n.b, n.c)),
+            Some((EV_ENVELOPE_REFUNDED, 1, ALICE, total))
+        );
+    }
+}
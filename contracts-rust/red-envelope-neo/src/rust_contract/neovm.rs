@@ -0,0 +1,263 @@
+const NULL_PROBE_KEY: i64 = -9_223_372_036_854_775_000i64;
+
+// Imports without a plain syscall alias are lowered by `scripts/patches` (see
+// `neo-red-envelope-imports`); account values are the contract's i64 account ids.
+#[cfg(target_arch = "wasm32")]
+#[link(wasm_import_module = "neo")]
+extern "C" {
+    #[link_name = "storage_get_context"]
+    fn neo_storage_get_context() -> i64;
+
+    // NOTE: argument order is reversed so NeoVM pops (ctx, key) in syscall order.
+    #[link_name = "storage_get"]
+    fn neo_storage_get(key: i64, ctx: i64) -> i64;
+
+    // NOTE: argument order is reversed so NeoVM pops (ctx, value, key) in syscall order.
+    #[link_name = "storage_put"]
+    fn neo_storage_put(key: i64, value: i64, ctx: i64);
+
+    #[link_name = "runtime_get_time"]
+    fn neo_get_time() -> i64;
+
+    #[link_name = "runtime_get_random"]
+    fn neo_get_random() -> i64;
+
+    // CryptoLib.sha256 over the little-endian (seed, counter) pair, folded to the first 8 digest bytes.
+    // NOTE: argument order is reversed so NeoVM pops (seed, counter) in call order.
+    #[link_name = "crypto_sha256_pair"]
+    fn neo_sha256_pair(counter: i64, seed: i64) -> i64;
+
+    // CryptoLib.sha256 over a raw ByteString, folded to the first 8 digest bytes.
+    #[link_name = "crypto_sha256_raw"]
+    fn neo_sha256_raw(raw: i64) -> i64;
+
+    #[link_name = "runtime_check_witness_hash"]
+    fn neo_check_witness(account: i64) -> i64;
+
+    // LedgerContract.currentIndex.
+    #[link_name = "ledger_current_index"]
+    fn neo_current_index() -> i64;
+
+    // LedgerContract.getBlock(index).Nonce, folded to its low 8 bytes.
+    #[link_name = "ledger_get_block_nonce"]
+    fn neo_block_nonce(index: i64) -> i64;
+
+    // NOTE: argument order is reversed so NeoVM pops (eventName, state) in syscall order;
+    // the i64 notify shim packs (a, b, c) into the state array under the `EV_*` code.
+    #[link_name = "runtime_notify"]
+    fn neo_notify(c: i64, b: i64, a: i64, event: i64);
+
+    #[link_name = "runtime_get_calling_script_hash"]
+    fn neo_calling_script_hash() -> i64;
+
+    #[link_name = "runtime_get_entry_script_hash"]
+    fn neo_entry_script_hash() -> i64;
+
+    // ContractManagement.getContract, lowered to 1 when a contract is deployed at `hash`.
+    #[link_name = "contract_get_contract"]
+    fn neo_get_contract(hash: i64) -> i64;
+
+    // NOTE: argument order is reversed so NeoVM pops (nef, manifest) in call order.
+    #[link_name = "contract_update"]
+    fn neo_contract_update(manifest: i64, nef: i64);
+
+    // NEWARRAY0; the handle is the Array stack item itself.
+    #[link_name = "array_new"]
+    fn neo_array_new() -> i64;
+
+    // APPEND; NeoVM pops (value, array).
+    #[link_name = "array_append"]
+    fn neo_array_append(array: i64, value: i64);
+}
+
+// Host builds (tests, clippy, tooling) never run the NeoVM wrapper; these keep it linkable.
+#[cfg(not(target_arch = "wasm32"))]
+unsafe fn neo_storage_get_context() -> i64 {
+    0
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+unsafe fn neo_storage_get(_key: i64, _ctx: i64) -> i64 {
+    0
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+unsafe fn neo_storage_put(_key: i64, _value: i64, _ctx: i64) {}
+
+#[cfg(not(target_arch = "wasm32"))]
+unsafe fn neo_get_time() -> i64 {
+    0
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+unsafe fn neo_get_random() -> i64 {
+    1
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+unsafe fn neo_sha256_pair(_counter: i64, _seed: i64) -> i64 {
+    0
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+unsafe fn neo_sha256_raw(_raw: i64) -> i64 {
+    0
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+unsafe fn neo_check_witness(_account: i64) -> i64 {
+    0
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+unsafe fn neo_current_index() -> i64 {
+    0
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+unsafe fn neo_block_nonce(_index: i64) -> i64 {
+    0
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+unsafe fn neo_notify(_c: i64, _b: i64, _a: i64, _event: i64) {}
+
+#[cfg(not(target_arch = "wasm32"))]
+unsafe fn neo_calling_script_hash() -> i64 {
+    0
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+unsafe fn neo_entry_script_hash() -> i64 {
+    0
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+unsafe fn neo_get_contract(_hash: i64) -> i64 {
+    0
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+unsafe fn neo_contract_update(_manifest: i64, _nef: i64) {}
+
+#[cfg(not(target_arch = "wasm32"))]
+unsafe fn neo_array_new() -> i64 {
+    0
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+unsafe fn neo_array_append(_array: i64, _value: i64) {}
+
+// The executing contract's storage context and syscalls.
+struct NeoVmRuntime;
+
+impl red_envelope_core::Storage for NeoVmRuntime {
+    fn get(&self, key: i64) -> i64 {
+        let ctx = unsafe { neo_storage_get_context() };
+        let raw = unsafe { neo_storage_get(key, ctx) };
+        let null_probe = unsafe { neo_storage_get(NULL_PROBE_KEY, ctx) };
+        // Missing keys materialize as Null stack items; compare against a guaranteed-missing probe.
+        if raw == null_probe {
+            0
+        } else {
+            add1(raw) - 1
+        }
+    }
+
+    fn put(&mut self, key: i64, value: i64) {
+        unsafe {
+            let ctx = neo_storage_get_context();
+            neo_storage_put(key, value, ctx);
+        }
+    }
+
+    fn get_raw(&self, key: i64) -> i64 {
+        unsafe { neo_storage_get(key, neo_storage_get_context()) }
+    }
+}
+
+impl red_envelope_core::Runtime for NeoVmRuntime {
+    fn now_ms(&self) -> i64 {
+        unsafe { neo_get_time() }
+    }
+
+    fn entropy(&self) -> i64 {
+        let random = unsafe { neo_get_random() };
+
+        let mut entropy = if random < 0 {
+            if random == i64::MIN {
+                i64::MAX
+            } else {
+                random.abs()
+            }
+        } else {
+            random
+        };
+
+        if entropy == 0 {
+            entropy = 1;
+        }
+        entropy
+    }
+
+    fn sha256_pair(&self, counter: i64, seed: i64) -> i64 {
+        unsafe { neo_sha256_pair(counter, seed) }
+    }
+
+    fn sha256_raw(&self, raw: i64) -> i64 {
+        unsafe { neo_sha256_raw(raw) }
+    }
+
+    fn check_witness(&self, account: i64) -> bool {
+        unsafe { neo_check_witness(account) != 0 }
+    }
+
+    fn block_index(&self) -> i64 {
+        unsafe { neo_current_index() }
+    }
+
+    fn block_nonce(&self, index: i64) -> i64 {
+        unsafe { neo_block_nonce(index) }
+    }
+
+    fn is_contract_account(&self, account: i64) -> bool {
+        unsafe { neo_get_contract(account) != 0 }
+    }
+
+    fn calling_contract(&self) -> i64 {
+        unsafe { neo_calling_script_hash() }
+    }
+
+    fn is_direct_invocation(&self) -> bool {
+        unsafe { neo_calling_script_hash() == neo_entry_script_hash() }
+    }
+
+    fn notify(&mut self, event: i64, a: i64, b: i64, c: i64) {
+        unsafe { neo_notify(c, b, a, event) }
+    }
+
+    fn update_contract(&mut self, nef: i64, manifest: i64) {
+        unsafe { neo_contract_update(manifest, nef) }
+    }
+
+    fn array_new(&mut self) -> i64 {
+        unsafe { neo_array_new() }
+    }
+
+    fn array_append(&mut self, array: i64, value: i64) {
+        unsafe { neo_array_append(array, value) }
+    }
+
+    fn abort(&self) -> ! {
+        // A wasm trap faults the NeoVM invocation.
+        #[cfg(target_arch = "wasm32")]
+        core::arch::wasm32::unreachable();
+        #[cfg(not(target_arch = "wasm32"))]
+        unreachable!()
+    }
+}
+
+#[inline(never)]
+fn add1(v: i64) -> i64 {
+    v + 1
+}
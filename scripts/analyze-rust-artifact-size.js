@@ -102,7 +102,7 @@ async function analyzeRustArtifactSize(options = {}) {
   const root = options.rootDir || path.resolve(__dirname, "..");
 
   const artifactPaths = {
-    wasm: options.wasmPath || path.resolve(root, "contracts-rust/red-envelope-neo/target/wasm32-unknown-unknown/release/red_envelope_neo_rust.wasm"),
+    wasm: options.wasmPath || path.resolve(root, "contracts-rust/target/wasm32-unknown-unknown/release/red_envelope_neo_rust.wasm"),
     nef: options.nefPath || path.resolve(root, "contracts-rust/red-envelope-neo/build/RedEnvelopeRust.nef"),
     manifest:
       options.manifestPath || path.resolve(root, "contracts-rust/red-envelope-neo/build/RedEnvelopeRust.manifest.json"),
@@ -24,9 +24,10 @@ const Neon = require("@cityofzion/neon-js");
 
 const ROOT = path.resolve(__dirname, "..");
 const CONTRACT_DIR = path.resolve(ROOT, "contracts-rust/red-envelope-neo");
+const CORE_DIR = path.resolve(ROOT, "contracts-rust/red-envelope-core");
 const TOOLCHAIN_DIR = path.resolve(ROOT, ".toolchains/neo-llvm");
 const CURRENT_ARTIFACTS = {
-  wasm: path.resolve(CONTRACT_DIR, "target/wasm32-unknown-unknown/release/red_envelope_neo_rust.wasm"),
+  wasm: path.resolve(ROOT, "contracts-rust/target/wasm32-unknown-unknown/release/red_envelope_neo_rust.wasm"),
   nef: path.resolve(CONTRACT_DIR, "build/RedEnvelopeRust.nef"),
   manifest: path.resolve(CONTRACT_DIR, "build/RedEnvelopeRust.manifest.json"),
 };
@@ -102,32 +103,9 @@ function copyDir(srcDir, dstDir) {
   fs.cpSync(srcDir, dstDir, { recursive: true });
 }
 
-function removeReleaseProfile(cargoTomlPath) {
-  const lines = fs.readFileSync(cargoTomlPath, "utf8").split(/\r?\n/);
-  const out = [];
-  let inReleaseProfile = false;
-
-  for (const line of lines) {
-    const section = line.match(/^\s*\[([^\]]+)\]\s*$/);
-    if (section) {
-      const sectionName = section[1].trim();
-      if (sectionName === "profile.release") {
-        inReleaseProfile = true;
-        continue;
-      }
-      inReleaseProfile = false;
-    }
-    if (!inReleaseProfile) {
-      out.push(line);
-    }
-  }
-
-  fs.writeFileSync(cargoTomlPath, `${out.join("\n").trim()}\n`);
-}
-
 function rustBuild(contractDir) {
   run("rustup", ["target", "add", "wasm32-unknown-unknown"]);
-  run("cargo", ["build", "--manifest-path", path.resolve(contractDir, "Cargo.toml"), "--release", "--target", "wasm32-unknown-unknown"]);
+  run("cargo", ["build", "--manifest-path", path.resolve(contractDir, "Cargo.toml"), "--lib", "--release", "--target", "wasm32-unknown-unknown"]);
 }
 
 function translateWithToolchain(toolchainDir, wasmPath, nefPath, manifestPath, name, overlayPath = null) {
@@ -381,8 +359,10 @@ function main() {
   }
 
   const defaultProfileDir = path.resolve(workspace, "default-profile");
+  // Copied outside the workspace, the contract builds without the root size profile;
+  // its `../red-envelope-core` path dependency resolves to the sibling copy.
   copyDir(CONTRACT_DIR, defaultProfileDir);
-  removeReleaseProfile(path.resolve(defaultProfileDir, "Cargo.toml"));
+  copyDir(CORE_DIR, path.resolve(workspace, "red-envelope-core"));
   rustBuild(defaultProfileDir);
   const defaultProfileOutDir = path.resolve(defaultProfileDir, "build-default");
   fs.mkdirSync(defaultProfileOutDir, { recursive: true });
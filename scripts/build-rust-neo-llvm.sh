@@ -5,7 +5,7 @@ ROOT_DIR="$(cd "$(dirname "$0")/.." && pwd)"
 TOOLCHAIN_DIR="${ROOT_DIR}/.toolchains/neo-llvm"
 CONTRACT_DIR="${ROOT_DIR}/contracts-rust/red-envelope-neo"
 BUILD_DIR="${CONTRACT_DIR}/build"
-WASM_PATH="${CONTRACT_DIR}/target/wasm32-unknown-unknown/release/red_envelope_neo_rust.wasm"
+WASM_PATH="${ROOT_DIR}/contracts-rust/target/wasm32-unknown-unknown/release/red_envelope_neo_rust.wasm"
 OPT_WASM_PATH="${BUILD_DIR}/RedEnvelopeRust.opt.wasm"
 CSP_MANIFEST="${ROOT_DIR}/contracts/bin/sc/RedEnvelope.manifest.json"
 
@@ -15,6 +15,8 @@ RUSTFLAGS_TO_USE="${NEO_WASM_RUSTFLAGS:-${DEFAULT_RUSTFLAGS}}"
 # the control-flow/local-initialization fixes required for parity-safe `-Oz`.
 WASM_OPT_MODE="${NEO_WASM_OPT:-auto}"
 WASM_OPT_LEVEL="${NEO_WASM_OPT_LEVEL:-Oz}"
+# Comma-separated cargo features, e.g. `NEO_RUST_FEATURES=testnet` for private-net clock control.
+CARGO_FEATURES="${NEO_RUST_FEATURES:-}"
 
 if [[ ! -d "${TOOLCHAIN_DIR}" ]]; then
   mkdir -p "${ROOT_DIR}/.toolchains"
@@ -41,11 +43,18 @@ fi
 
 rustup target add wasm32-unknown-unknown >/dev/null 2>&1 || true
 
+FEATURE_ARGS=()
+if [[ -n "${CARGO_FEATURES}" ]]; then
+  FEATURE_ARGS=(--features "${CARGO_FEATURES}")
+fi
+
 echo "Building Rust contract with RUSTFLAGS: ${RUSTFLAGS_TO_USE}"
 RUSTFLAGS="${RUSTFLAGS_TO_USE}" cargo build \
   --manifest-path "${CONTRACT_DIR}/Cargo.toml" \
+  --lib \
   --release \
-  --target wasm32-unknown-unknown
+  --target wasm32-unknown-unknown \
+  "${FEATURE_ARGS[@]}"
 
 mkdir -p "${BUILD_DIR}"
 TRANSLATE_INPUT="${WASM_PATH}"